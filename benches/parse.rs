@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate criterion;
+extern crate tokei;
+
+use criterion::Criterion;
+use tokei::{LanguageType, Stats};
+
+/// The fixtures which are parsed, with their languages.
+const FIXTURES: &[(LanguageType, &str, &[u8])] = &[
+    (LanguageType::Rust, "rust.rs", include_bytes!("../tests/data/rust.rs")),
+    (LanguageType::C, "c.c", include_bytes!("../tests/data/c.c")),
+    (LanguageType::Html, "html.html", include_bytes!("../tests/data/html.html")),
+    (LanguageType::Python, "python.py", include_bytes!("../tests/data/python.py")),
+];
+
+/// Number of times each fixture is repeated, so that the time spent parsing
+/// outweighs the setup of a file.
+const REPEAT: usize = 1000;
+
+fn parse(language: LanguageType, name: &str, text: &[u8]) -> Stats {
+    language.parse_from_bytes(name.to_owned().into(), text).unwrap()
+}
+
+fn bench_parse(c: &mut Criterion) {
+    for &(language, name, fixture) in FIXTURES {
+        let text = fixture.repeat(REPEAT);
+
+        // Repeating a fixture shouldn't change how its lines are counted.
+        let stats = parse(language, name, fixture);
+        let repeated = parse(language, name, &text);
+        assert_eq!(repeated.lines, stats.lines * REPEAT, "{}", name);
+
+        c.bench_function(name, |b| b.iter(|| parse(language, name, &text)));
+    }
+}
+
+/// A large source file without any comments or quotes, where nearly all of
+/// the time is spent looking for syntax.
+fn bench_comment_free(c: &mut Criterion) {
+    let text = b"let value = compute(input, 42);\n".repeat(100_000);
+
+    let stats = parse(LanguageType::Rust, "comment_free.rs", &text);
+    assert_eq!(stats.code, 100_000);
+
+    c.bench_function("comment_free.rs", |b| {
+        b.iter(|| parse(LanguageType::Rust, "comment_free.rs", &text))
+    });
+}
+
+/// A 34 MB Rust file, where checking for blank lines takes up a noticeable
+/// part of the time.
+fn bench_large(c: &mut Criterion) {
+    let (language, name, fixture) = FIXTURES[0];
+    let text = fixture.repeat(50_000);
+
+    let mut group = c.benchmark_group("large");
+    group.sample_size(10);
+    group.bench_function(name, |b| b.iter(|| parse(language, name, &text)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_comment_free, bench_large);
+criterion_main!(benches);
@@ -46,8 +46,17 @@ fn generate_languages(out_dir: &OsStr) -> Result<(), Box<error::Error>>  {
 
         sort_prop!("quotes");
         sort_prop!("multi_line");
+        sort_prop!("doc_multi_line");
+        sort_prop!("verbatim_quotes");
+        sort_prop!("literal_quotes");
+        sort_prop!("raw_quotes");
     }
 
+    // Hashed once sorted, so only changes to the definitions themselves
+    // change the fingerprint.
+    let version = format!("{:016x}", fnv1a(serde_json::to_string(&json)?.as_bytes()));
+    json["definitions_version"] = Value::String(version);
+
     let output = Path::new(&out_dir).join("language_type.rs");
     let mut source_template = File::open(&"src/language/language_type.hbs.rs")?;
     let mut output_file = File::create(&output)?;
@@ -58,6 +67,14 @@ fn generate_languages(out_dir: &OsStr) -> Result<(), Box<error::Error>>  {
     Ok(())
 }
 
+/// The 64 bit FNV-1a hash, which unlike the hashers of `std` is stable across
+/// Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 fn compare_json_str_len(a: &Value, b: &Value) -> cmp::Ordering {
     let a = a.as_array().expect("a as array");
     let b = b.as_array().expect("b as array");
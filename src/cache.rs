@@ -0,0 +1,117 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::time::SystemTime;
+
+use config::ParseConfig;
+use language::{definitions_version, LanguageType};
+use stats::Stats;
+
+/// The statistics of previously counted files, so that files which haven't
+/// changed since aren't parsed again. Set on `Languages` with
+/// `Languages::set_cache`, after which the statistics methods use and update
+/// it.
+///
+/// A file is only parsed again when its modification time or size changed,
+/// or when the `ParseConfig` or `definitions_version` it was counted with
+/// did, which is remembered by the cache. Changes made within the resolution
+/// of the file system's timestamps which keep the size can still be missed,
+/// so the cache should be cleared when that matters. Only files whose
+/// `FileAccess` provides `modified` are cached, while files of custom
+/// languages, and files which were too large to be read or skipped as
+/// generated, never are.
+///
+/// Each run of the statistics methods replaces the cache with the files
+/// counted by that run, so files which were removed or are no longer walked
+/// are dropped.
+///
+/// ```no_run
+/// # use tokei::*;
+/// let mut languages = Languages::new();
+/// languages.set_cache(StatsCache::new());
+/// languages.get_statistics(&["."], vec![".git", "target"], None);
+///
+/// // Only files which changed are parsed again.
+/// let cache = languages.take_cache().unwrap();
+/// let mut languages = Languages::new();
+/// languages.set_cache(cache);
+/// languages.get_statistics(&["."], vec![".git", "target"], None);
+/// ```
+#[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
+#[derive(Clone, Debug, Default)]
+pub struct StatsCache {
+    /// Hash of the settings the files were counted with.
+    #[cfg_attr(feature = "io", serde(default))]
+    fingerprint: u64,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
+#[derive(Clone, Debug)]
+struct CacheEntry {
+    modified: SystemTime,
+    size: u64,
+    language: LanguageType,
+    stats: Stats,
+}
+
+impl StatsCache {
+    /// Constructs a new, empty cache.
+    pub fn new() -> Self {
+        StatsCache::default()
+    }
+
+    /// Constructs an empty cache for files counted with `config`.
+    pub(crate) fn with_config(config: &ParseConfig) -> Self {
+        StatsCache { fingerprint: fingerprint(config), entries: BTreeMap::new() }
+    }
+
+    /// Whether the files in the cache were counted with `config`.
+    pub(crate) fn is_for(&self, config: &ParseConfig) -> bool {
+        self.fingerprint == fingerprint(config)
+    }
+
+    /// Number of files in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no files in the cache.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every file from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The language and statistics of the file named `name`, if it hasn't
+    /// changed since it was cached.
+    pub(crate) fn get(&self, name: &str, modified: SystemTime, size: u64)
+        -> Option<(LanguageType, &Stats)>
+    {
+        self.entries.get(name)
+            .filter(|entry| entry.modified == modified && entry.size == size)
+            .map(|entry| (entry.language, &entry.stats))
+    }
+
+    pub(crate) fn insert(&mut self,
+                         name: String,
+                         modified: SystemTime,
+                         size: u64,
+                         language: LanguageType,
+                         stats: Stats)
+    {
+        self.entries.insert(name, CacheEntry { modified, size, language, stats });
+    }
+}
+
+/// Hash of `config` and the language definitions, which both decide how files
+/// are counted.
+fn fingerprint(config: &ParseConfig) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    definitions_version().hash(&mut hasher);
+    config.hash(&mut hasher);
+    hasher.finish()
+}
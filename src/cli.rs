@@ -49,7 +49,7 @@ impl<'a> Cli<'a> {
                 "Outputs Tokei in a specific format. Compile with additional features for more \
                 format support.")
             (@arg sort: -s --sort
-                possible_values(&["files", "lines", "blanks", "code", "comments"])
+                possible_values(&["files", "lines", "blanks", "code", "comments", "name"])
                 case_insensitive(true)
                 +takes_value
                 "Sort languages based on column")
@@ -123,7 +123,7 @@ impl<'a> Cli<'a> {
 
     pub fn print_supported_languages() {
         for key in LanguageType::list() {
-            println!("{:<25}", key);
+            println!("{:<25} {}", key.name(), key.extensions().join(", "));
         }
     }
 
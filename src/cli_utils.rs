@@ -106,7 +106,7 @@ pub fn print_language<W>(sink: &mut W,
     }
     writeln!(sink,
              "{:>6} {:>12} {:>12} {:>12} {:>12}",
-             language.stats.len(),
+             language.file_count(),
              language.lines,
              language.code,
              language.comments,
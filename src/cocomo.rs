@@ -0,0 +1,96 @@
+/// The coefficients of the basic COCOMO model used by `Languages::cocomo`.
+/// Defaults to the organic model, for small teams working on familiar
+/// software.
+///
+/// ```
+/// # use tokei::*;
+/// // The semi-detached model.
+/// let params = CocomoParams {
+///     effort_coefficient: 3.0,
+///     effort_exponent: 1.12,
+///     schedule_exponent: 0.35,
+///     ..CocomoParams::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CocomoParams {
+    /// Multiplier of the effort. Defaults to `2.4`.
+    pub effort_coefficient: f64,
+    /// Exponent of the thousands of lines of code in the effort. Defaults to
+    /// `1.05`.
+    pub effort_exponent: f64,
+    /// Multiplier of the schedule. Defaults to `2.5`.
+    pub schedule_coefficient: f64,
+    /// Exponent of the effort in the schedule. Defaults to `0.38`.
+    pub schedule_exponent: f64,
+    /// Average yearly salary of a developer. Defaults to `56286`.
+    pub average_salary: f64,
+    /// Multiplier of the salaries for the other costs of a developer, like
+    /// equipment and offices. Defaults to `2.4`.
+    pub overhead: f64,
+}
+
+impl Default for CocomoParams {
+    fn default() -> Self {
+        CocomoParams {
+            effort_coefficient: 2.4,
+            effort_exponent: 1.05,
+            schedule_coefficient: 2.5,
+            schedule_exponent: 0.38,
+            average_salary: 56286.0,
+            overhead: 2.4,
+        }
+    }
+}
+
+/// A rough estimate of what it would cost to develop some code, as created
+/// by `Languages::cocomo`.
+#[cfg_attr(feature = "io", derive(Serialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CocomoEstimate {
+    /// Effort in person-months.
+    pub effort: f64,
+    /// Time of development in months.
+    pub schedule: f64,
+    /// Average number of developers during the development.
+    pub people: f64,
+    /// Cost of development, in the currency of `CocomoParams::average_salary`.
+    pub cost: f64,
+}
+
+impl CocomoEstimate {
+    /// Estimates the development of `code` lines of code.
+    pub(crate) fn new(code: usize, params: CocomoParams) -> Self {
+        if code == 0 {
+            return CocomoEstimate::default();
+        }
+
+        let kloc = code as f64 / 1000.0;
+        let effort = params.effort_coefficient * kloc.powf(params.effort_exponent);
+        let schedule = params.schedule_coefficient * effort.powf(params.schedule_exponent);
+
+        CocomoEstimate {
+            effort,
+            schedule,
+            people: effort / schedule,
+            cost: effort / 12.0 * params.average_salary * params.overhead,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn organic() {
+        let estimate = CocomoEstimate::new(10_000, CocomoParams::default());
+
+        assert!((estimate.effort - 26.93).abs() < 0.01);
+        assert!((estimate.schedule - 8.74).abs() < 0.01);
+        assert!((estimate.people - 3.08).abs() < 0.01);
+        assert!((estimate.cost - 303_138.87).abs() < 0.01);
+
+        assert_eq!(CocomoEstimate::new(0, CocomoParams::default()), CocomoEstimate::default());
+    }
+}
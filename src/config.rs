@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+
+use language::LanguageType;
+use utils::bytes::{self, Bytes, FIRST_FEW_BYTES};
+use FileAccess;
+
+const DEFAULT_TODO_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Configuration of how files are parsed.
+///
+/// ```
+/// # use tokei::*;
+/// let config = ParseConfig {
+///     binary_sniff_bytes: 1024,
+///     ..ParseConfig::default()
+/// };
+/// ```
+#[derive(Clone, Debug, Hash)]
+pub struct ParseConfig {
+    /// Number of bytes at the start of a file which are checked when deciding
+    /// whether the file is binary. Defaults to `8000`.
+    pub binary_sniff_bytes: usize,
+    /// Whether files starting with a UTF-16 or UTF-32 byte order mark are
+    /// treated as text, even though they contain null bytes. Defaults to
+    /// `true`.
+    pub treat_utf16_as_text: bool,
+    /// Markers which are counted in `Stats::todos` when they occur in a
+    /// comment. Defaults to `TODO`, `FIXME`, `HACK` and `XXX`.
+    pub todo_markers: Vec<String>,
+    /// Whether the lines of generated files aren't counted. These files are
+    /// still returned, with `Stats::generated` set and only `bytes` counted.
+    /// Defaults to `false`.
+    pub skip_generated: bool,
+    /// Markers which identify a file as generated when they occur within its
+    /// first `generated_sniff_lines` lines. Defaults to `@generated`.
+    pub generated_markers: Vec<String>,
+    /// Number of lines at the start of a file which are checked for the
+    /// `generated_markers`. Defaults to `5`.
+    pub generated_sniff_lines: usize,
+    /// Extensions and file names which are counted as another language than
+    /// the one they are detected as. Defaults to none.
+    pub overrides: ExtensionOverrides,
+    /// Whether a shebang (`#!`) on the first line of a file is counted as a
+    /// comment. Otherwise it's counted as code, even in languages where `#`
+    /// starts a comment. Defaults to `false`.
+    pub count_shebang_as_comment: bool,
+    /// Size in bytes above which files aren't read. These files are still
+    /// returned, with `Stats::too_large` set and nothing counted. Defaults to
+    /// no limit.
+    pub max_file_bytes: Option<usize>,
+    /// Languages whose files are skipped, even if they're allowed by the
+    /// `types` given when parsing. Defaults to none.
+    pub excluded_types: Vec<LanguageType>,
+    /// Whether an Emacs (`-*- mode: python -*-`) or Vim (`vim: set ft=ruby :`)
+    /// modeline within the first or last few lines of a file decides its
+    /// language, see `LanguageType::from_modeline`. A modeline takes
+    /// precedence over the file's name, extension and shebang, but not over
+    /// `overrides`. Every file is then read before its language is known.
+    /// Defaults to `false`.
+    pub detect_modelines: bool,
+    /// Number of columns between tab stops when measuring
+    /// `Stats::max_line_length`, so that it matches the columns shown by an
+    /// editor. Defaults to `1`, measuring lines in bytes.
+    pub tab_width: usize,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        ParseConfig {
+            binary_sniff_bytes: FIRST_FEW_BYTES,
+            treat_utf16_as_text: true,
+            todo_markers: DEFAULT_TODO_MARKERS.iter().map(|m| m.to_string()).collect(),
+            skip_generated: false,
+            generated_markers: vec![String::from("@generated")],
+            generated_sniff_lines: 5,
+            overrides: ExtensionOverrides::default(),
+            count_shebang_as_comment: false,
+            max_file_bytes: None,
+            excluded_types: Vec::new(),
+            detect_modelines: false,
+            tab_width: 1,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// Test whether the start of a file is binary according to this
+    /// configuration.
+    pub(crate) fn is_binary(&self, bytes: &[u8]) -> bool {
+        bytes::is_binary_with(bytes, self.binary_sniff_bytes, self.treat_utf16_as_text)
+    }
+
+    /// Test whether files of `language` are counted, given the allowed
+    /// `types` if any. Excluded languages never are.
+    pub(crate) fn is_supported(&self, types: Option<&[LanguageType]>, language: LanguageType)
+        -> bool
+    {
+        types.map(|t| t.contains(&language)).unwrap_or(true)
+            && !self.excluded_types.contains(&language)
+    }
+
+    /// Reads the rest of `reader` into `text`, unless the whole text would be
+    /// larger than `max_file_bytes`. Returns whether it wasn't.
+    pub(crate) fn read_to_end<R: Read>(&self, mut reader: R, text: &mut Vec<u8>)
+        -> io::Result<bool>
+    {
+        match self.max_file_bytes {
+            Some(max) => {
+                let limit = max.saturating_add(1).saturating_sub(text.len());
+                reader.take(limit as u64).read_to_end(text)?;
+                Ok(text.len() <= max)
+            }
+            None => {
+                reader.read_to_end(text)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Test whether the text should be skipped as generated according to
+    /// this configuration.
+    pub(crate) fn is_skipped_generated(&self, text: Bytes) -> bool {
+        self.skip_generated && text.lines().take(self.generated_sniff_lines).any(|line| {
+            self.generated_markers.iter().any(|marker| line.contains(marker.as_bytes()))
+        })
+    }
+}
+
+/// Extensions and file names which are counted as a chosen language, taking
+/// precedence over the language they would be detected as. Both are matched
+/// case insensitively, and extensions are given without the leading `.`.
+///
+/// ```
+/// # use tokei::*;
+/// # use std::path::Path;
+/// let mut overrides = ExtensionOverrides::default();
+/// overrides.extensions.insert("h".to_owned(), LanguageType::CppHeader);
+///
+/// let language = overrides.get(Path::new("src/main.h"));
+/// assert_eq!(language, Some(LanguageType::CppHeader));
+/// ```
+#[derive(Clone, Debug, Default, Hash)]
+pub struct ExtensionOverrides {
+    /// The languages of files with these extensions.
+    pub extensions: BTreeMap<String, LanguageType>,
+    /// The languages of files with these names. These take precedence over
+    /// `extensions`.
+    pub file_names: BTreeMap<String, LanguageType>,
+}
+
+impl ExtensionOverrides {
+    /// Whether there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty() && self.file_names.is_empty()
+    }
+
+    /// The language the file is overridden to, if any.
+    pub fn get<'a, F: FileAccess<'a>>(&self, file_access: F) -> Option<LanguageType> {
+        fn find(map: &BTreeMap<String, LanguageType>, key: &str) -> Option<LanguageType> {
+            map.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, &language)| language)
+        }
+
+        if self.is_empty() {
+            return None;
+        }
+
+        file_access.file_name()
+            .and_then(|name| find(&self.file_names, &name))
+            .or_else(|| file_access.extension().and_then(|ext| find(&self.extensions, &ext)))
+    }
+}
+
+/// Configuration of how directories are walked when looking for files.
+///
+/// By default, files ignored by `.gitignore` and `.ignore` files, and hidden
+/// files, are skipped. To count everything, including ignored build output:
+///
+/// ```
+/// # use tokei::*;
+/// let config = WalkConfig {
+///     git_ignore: false,
+///     hidden: false,
+///     ignore: false,
+///     parents: false,
+///     ..WalkConfig::default()
+/// };
+/// ```
+#[derive(Clone, Debug)]
+pub struct WalkConfig {
+    /// Whether `.gitignore` files, the global git ignore file and
+    /// `.git/info/exclude` are respected. Defaults to `true`.
+    pub git_ignore: bool,
+    /// Whether hidden files and directories are skipped. Defaults to `true`.
+    pub hidden: bool,
+    /// Whether `.ignore` and `.tokeignore` files are respected. Defaults to
+    /// `true`.
+    pub ignore: bool,
+    /// Whether ignore files in parent directories of the provided paths are
+    /// respected. Defaults to `true`.
+    pub parents: bool,
+    /// Number of threads used to walk directories and parse files. `0`
+    /// uses the default, which is based on the number of CPUs. Defaults to
+    /// `0`.
+    pub threads: usize,
+    /// Whether symbolic links are followed. Files reachable through several
+    /// links are only counted once, and links pointing to one of their own
+    /// parent directories are reported as errors rather than followed.
+    /// Defaults to `false`.
+    pub follow_links: bool,
+    /// Whether the git repository containing the first of the provided paths
+    /// anchors the ignore handling, so that the same files are ignored
+    /// whichever directory tokei is run from. The repository is found by
+    /// looking for a `.git` directory or file in the parent directories.
+    /// Ignore files in the parent directories are then respected, regardless
+    /// of `parents`, and globs are matched relative to the root of the
+    /// repository instead of the current directory. Only the provided paths
+    /// are counted. Without a repository this has no effect. Defaults to
+    /// `false`.
+    pub vcs_root: bool,
+}
+
+impl Default for WalkConfig {
+    fn default() -> Self {
+        WalkConfig {
+            git_ignore: true,
+            hidden: true,
+            ignore: true,
+            parents: true,
+            threads: 0,
+            follow_links: false,
+            vcs_root: false,
+        }
+    }
+}
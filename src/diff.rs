@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use language::{Language, LanguageType, Languages};
+
+/// The change in counts of a single language between two `Languages`.
+#[cfg_attr(feature = "io", derive(Serialize))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LanguageDelta {
+    /// Change in number of blank lines.
+    pub blanks: i64,
+    /// Change in number of lines of code.
+    pub code: i64,
+    /// Change in number of comments.
+    pub comments: i64,
+    /// Change in number of files.
+    pub files: i64,
+    /// Change in total number of lines.
+    pub lines: i64,
+}
+
+impl LanguageDelta {
+    fn between(current: Option<&Language>, previous: Option<&Language>) -> Self {
+        let count = |language: Option<&Language>, f: fn(&Language) -> usize| {
+            language.map(f).unwrap_or(0) as i64
+        };
+        let delta = |f: fn(&Language) -> usize| count(current, f) - count(previous, f);
+
+        LanguageDelta {
+            blanks: delta(|l| l.blanks),
+            code: delta(|l| l.code),
+            comments: delta(|l| l.comments),
+            files: delta(|l| l.file_count()),
+            lines: delta(|l| l.lines),
+        }
+    }
+
+    /// Whether none of the counts changed.
+    pub fn is_empty(&self) -> bool {
+        *self == LanguageDelta::default()
+    }
+}
+
+/// The difference between two `Languages`, as created by `Languages::diff`.
+#[cfg_attr(feature = "io", derive(Serialize))]
+#[derive(Clone, Debug, Default)]
+pub struct LanguageDiff {
+    /// Languages which only have statistics in the current `Languages`.
+    pub added: Vec<LanguageType>,
+    /// Languages which only have statistics in the previous `Languages`.
+    pub removed: Vec<LanguageType>,
+    /// Changes of every language present in either `Languages`, including
+    /// added and removed ones.
+    pub languages: BTreeMap<LanguageType, LanguageDelta>,
+}
+
+impl LanguageDiff {
+    pub(crate) fn new(current: &Languages, previous: &Languages) -> Self {
+        let mut diff = LanguageDiff::default();
+        let names = current.keys().chain(previous.keys());
+
+        for name in names {
+            if diff.languages.contains_key(name) {
+                continue;
+            }
+
+            let current = present(current, name);
+            let previous = present(previous, name);
+
+            match (current, previous) {
+                (None, None) => continue,
+                (Some(_), None) => diff.added.push(*name),
+                (None, Some(_)) => diff.removed.push(*name),
+                (Some(_), Some(_)) => {}
+            }
+
+            diff.languages.insert(*name, LanguageDelta::between(current, previous));
+        }
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff
+    }
+}
+
+fn present<'a>(languages: &'a Languages, name: &LanguageType) -> Option<&'a Language> {
+    languages.get(name).filter(|l| !l.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stats::Stats;
+
+    fn language(code: usize, comments: usize, files: usize) -> Language {
+        let mut language = Language::new();
+        language.code = code;
+        language.comments = comments;
+        language.lines = code + comments;
+        language.stats = vec![Stats::new(String::new()); files];
+        language
+    }
+
+    #[test]
+    fn diff() {
+        let mut previous = Languages::new();
+        previous.insert(LanguageType::Rust, language(10, 2, 1));
+        previous.insert(LanguageType::C, language(5, 0, 1));
+        previous.insert(LanguageType::Python, Language::new());
+
+        let mut current = Languages::new();
+        current.insert(LanguageType::Rust, language(15, 1, 2));
+        current.insert(LanguageType::Go, language(3, 1, 1));
+
+        let diff = current.diff(&previous);
+
+        assert_eq!(diff.added, vec![LanguageType::Go]);
+        assert_eq!(diff.removed, vec![LanguageType::C]);
+        assert_eq!(diff.languages.len(), 3);
+
+        let rust = diff.languages[&LanguageType::Rust];
+        assert_eq!((rust.code, rust.comments, rust.lines, rust.files), (5, -1, 4, 1));
+
+        let c = diff.languages[&LanguageType::C];
+        assert_eq!((c.code, c.lines, c.files), (-5, -5, -1));
+
+        let go = diff.languages[&LanguageType::Go];
+        assert_eq!((go.code, go.comments, go.files), (3, 1, 1));
+    }
+}
@@ -2,6 +2,10 @@ use std::io;
 use std::fs;
 use std::path::Path;
 use std::borrow::Cow;
+use std::time::SystemTime;
+
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
 
 impl<'a> FileAccess<'a> for &'a Path {
     type Reader = fs::File;
@@ -31,6 +35,11 @@ impl<'a> FileAccess<'a> for &'a Path {
             None => None
         }
     }
+
+    fn modified(self) -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(self).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
 }
 
 /// Trait to access files for analysis.
@@ -47,11 +56,12 @@ pub trait FileAccess<'a>: Copy {
     /// Get the name of the file object.
     fn name(self) -> Cow<'a, str>;
 
-    /// Access the file name, if available.
+    /// Access the file name, if available. By default this is the part of
+    /// `name` after the last `/` or `\\`, whichever platform tokei runs on.
     fn file_name(self) -> Option<Cow<'a, str>> {
         let name = match self.name() {
-            Cow::Borrowed(n) => Cow::from(n.rsplit("/").next().unwrap_or_else(|| n)),
-            Cow::Owned(n) => Cow::from(n.rsplit("/").next().unwrap_or_else(|| &n).to_string()),
+            Cow::Borrowed(n) => Cow::from(base_name(n)),
+            Cow::Owned(n) => Cow::from(base_name(&n).to_string()),
         };
 
         Some(name)
@@ -66,6 +76,13 @@ pub trait FileAccess<'a>: Copy {
         }
     }
 
+    /// The modification time and size of the file, if available. These are
+    /// used by `StatsCache` to tell whether the file changed, so files
+    /// without them are always parsed.
+    fn modified(self) -> Option<(SystemTime, u64)> {
+        None
+    }
+
     /// Rename the file access object.
     fn with_name(self, name: &'a str) -> WithName<'a, Self> {
         WithName {
@@ -94,4 +111,376 @@ impl<'a, F> FileAccess<'a> for WithName<'a, F> where F: FileAccess<'a> {
     fn name(self) -> Cow<'a, str> {
         Cow::from(self.name)
     }
+
+    fn modified(self) -> Option<(SystemTime, u64)> {
+        self.file_access.modified()
+    }
+}
+
+/// The part of a path after its last separator. Both `/` and `\\` are
+/// separators, so that names of files from Windows are understood on other
+/// platforms too.
+fn base_name(name: &str) -> &str {
+    name.rsplit(&['/', '\\'][..]).next().unwrap_or(name)
+}
+
+/// A file held in memory, named by its path.
+///
+/// The language is detected from the name like for any other file, or from
+/// the contents (e.g. a shebang) if the name doesn't have a known extension.
+///
+/// ```
+/// # use tokei::*;
+/// let files = vec![
+///     MemoryFile { name: "src/main.rs", contents: b"fn main() {}\n" },
+///     MemoryFile { name: "bin/run", contents: b"#!/usr/bin/env python\nprint(1)\n" },
+/// ];
+///
+/// let mut languages = Languages::new();
+/// languages.get_statistics_from(files, None);
+///
+/// assert_eq!(languages[&LanguageType::Rust].code, 1);
+/// assert_eq!(languages[&LanguageType::Python].code, 2);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryFile<'a> {
+    /// The path of the file.
+    pub name: &'a str,
+    /// The contents of the file.
+    pub contents: &'a [u8],
+}
+
+impl<'a> FileAccess<'a> for MemoryFile<'a> {
+    type Reader = io::Cursor<&'a [u8]>;
+
+    fn open(self) -> io::Result<Self::Reader> {
+        Ok(io::Cursor::new(self.contents))
+    }
+
+    fn name(self) -> Cow<'a, str> {
+        Cow::from(self.name)
+    }
+
+    fn file_name(self) -> Option<Cow<'a, str>> {
+        FileAccess::file_name(Path::new(base_name(self.name)))
+    }
+
+    fn extension(self) -> Option<Cow<'a, str>> {
+        FileAccess::extension(Path::new(base_name(self.name)))
+    }
+}
+
+/// Access to a gzip compressed file, which is transparently decompressed when
+/// opened.
+///
+/// The `.gz` extension is stripped from the name, so the language is detected
+/// from the name of the compressed file, e.g. `foo.js.gz` is treated as
+/// `foo.js`.
+#[cfg(feature = "gzip")]
+#[derive(Clone, Copy)]
+pub struct GzFileAccess<'a> {
+    path: &'a Path,
+}
+
+#[cfg(feature = "gzip")]
+impl<'a> GzFileAccess<'a> {
+    /// Construct a new `GzFileAccess` for the file at the given path.
+    pub fn new(path: &'a Path) -> Self {
+        GzFileAccess {
+            path,
+        }
+    }
+
+    /// The path of the decompressed file, if the path ends with `.gz`.
+    fn inner_path(self) -> &'a Path {
+        match self.path.extension() {
+            Some(extension) if extension == "gz" => {
+                self.path.file_stem().map(Path::new).unwrap_or(self.path)
+            },
+            _ => self.path,
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<'a> FileAccess<'a> for GzFileAccess<'a> {
+    type Reader = GzDecoder<fs::File>;
+
+    fn open(self) -> io::Result<Self::Reader> {
+        Ok(GzDecoder::new(fs::File::open(self.path)?))
+    }
+
+    fn name(self) -> Cow<'a, str> {
+        let name = self.path.to_string_lossy();
+
+        match name {
+            Cow::Borrowed(n) if n.ends_with(".gz") => Cow::from(&n[..n.len() - 3]),
+            Cow::Owned(ref n) if n.ends_with(".gz") => Cow::from(n[..n.len() - 3].to_string()),
+            name => name,
+        }
+    }
+
+    fn file_name(self) -> Option<Cow<'a, str>> {
+        FileAccess::file_name(self.inner_path())
+    }
+
+    fn extension(self) -> Option<Cow<'a, str>> {
+        FileAccess::extension(self.inner_path())
+    }
+
+    fn modified(self) -> Option<(SystemTime, u64)> {
+        FileAccess::modified(self.path)
+    }
+}
+
+/// A regular file read from a tar archive.
+///
+/// Created using [`tar_entries`]. The contents of the entry are held in
+/// memory, since the entries of an archive can only be read in order.
+#[cfg(feature = "tar")]
+#[derive(Clone, Debug)]
+pub struct TarEntry {
+    name: String,
+    contents: Vec<u8>,
+}
+
+#[cfg(feature = "tar")]
+impl<'a> FileAccess<'a> for &'a TarEntry {
+    type Reader = io::Cursor<&'a [u8]>;
+
+    fn open(self) -> io::Result<Self::Reader> {
+        Ok(io::Cursor::new(&self.contents))
+    }
+
+    fn name(self) -> Cow<'a, str> {
+        Cow::from(&*self.name)
+    }
+}
+
+/// Read every regular file out of a tar archive, named by its path inside of
+/// the archive.
+///
+/// Entries of a tar archive are read sequentially from the underlying reader,
+/// so they can't be opened later or in parallel. Therefore each entry is
+/// buffered into memory as it is read, after which the entries can be passed
+/// to [`Languages::get_statistics_from`](struct.Languages.html#method.get_statistics_from).
+///
+/// ```no_run
+/// # extern crate tar;
+/// # extern crate tokei;
+/// # use tokei::*;
+/// let file = std::fs::File::open("release.tar").unwrap();
+/// let entries = tar_entries(&mut tar::Archive::new(file)).unwrap();
+///
+/// let mut languages = Languages::new();
+/// languages.get_statistics_from(&entries, None);
+/// ```
+#[cfg(feature = "tar")]
+pub fn tar_entries<R: io::Read>(archive: &mut tar::Archive<R>) -> io::Result<Vec<TarEntry>> {
+    use std::io::Read;
+
+    let mut entries = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+
+        entries.push(TarEntry {
+            name,
+            contents,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// A file read from a zip archive.
+///
+/// Created using [`zip_entries`]. The contents of the entry are held in
+/// memory, since a `ZipArchive` can only read one entry at a time.
+#[cfg(feature = "zip")]
+#[derive(Clone, Debug)]
+pub struct ZipEntry {
+    name: String,
+    contents: Vec<u8>,
+}
+
+#[cfg(feature = "zip")]
+impl<'a> FileAccess<'a> for &'a ZipEntry {
+    type Reader = io::Cursor<&'a [u8]>;
+
+    fn open(self) -> io::Result<Self::Reader> {
+        Ok(io::Cursor::new(&self.contents))
+    }
+
+    fn name(self) -> Cow<'a, str> {
+        Cow::from(&*self.name)
+    }
+}
+
+/// Read every file out of a zip archive, named by its path inside of the
+/// archive. Directories are skipped.
+///
+/// Entries of a zip archive are read by index through a mutable borrow of the
+/// archive, so they can't be opened in parallel. Therefore each entry is
+/// buffered into memory, after which the entries can be passed to
+/// [`Languages::get_statistics_from`](struct.Languages.html#method.get_statistics_from).
+///
+/// ```no_run
+/// # extern crate zip;
+/// # extern crate tokei;
+/// # use tokei::*;
+/// let file = std::fs::File::open("release.zip").unwrap();
+/// let entries = zip_entries(&mut zip::ZipArchive::new(file).unwrap()).unwrap();
+///
+/// let mut languages = Languages::new();
+/// languages.get_statistics_from(&entries, None);
+/// ```
+#[cfg(feature = "zip")]
+pub fn zip_entries<R>(archive: &mut zip::ZipArchive<R>) -> io::Result<Vec<ZipEntry>>
+    where R: io::Read + io::Seek
+{
+    use std::io::Read;
+
+    let mut entries = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().to_owned();
+        // The size is only a hint from the archive, which could be crafted.
+        let capacity = entry.size().min(1 << 20) as usize;
+        let mut contents = Vec::with_capacity(capacity);
+        entry.read_to_end(&mut contents)?;
+
+        entries.push(ZipEntry {
+            name,
+            contents,
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::LanguageType;
+
+    #[test]
+    fn memory_file() {
+        let file = MemoryFile { name: "docker/Dockerfile", contents: b"FROM rust\n" };
+
+        assert_eq!(file.file_name().unwrap(), "dockerfile");
+
+        let (language, stats) = LanguageType::parse(file, None).unwrap().unwrap();
+        assert_eq!(language, LanguageType::Dockerfile);
+        assert_eq!(stats.code, 1);
+        assert_eq!(stats.name, "docker/Dockerfile");
+    }
+
+    #[test]
+    fn backslash_separators() {
+        let path = Path::new("main.rs");
+        let file = path.with_name("C:\\foo\\bar.rs");
+        assert_eq!(file.file_name().unwrap(), "bar.rs");
+        assert_eq!(LanguageType::from_file_access(file), Some(LanguageType::Rust));
+
+        let file = path.with_name("C:\\foo.d\\Makefile");
+        assert_eq!(file.file_name().unwrap(), "Makefile");
+        assert_eq!(LanguageType::from_file_access(file), Some(LanguageType::Makefile));
+
+        let file = MemoryFile { name: "docker\\Dockerfile", contents: b"FROM rust\n" };
+        assert_eq!(file.file_name().unwrap(), "dockerfile");
+        assert_eq!(LanguageType::from_file_access(file), Some(LanguageType::Dockerfile));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_file_access() {
+        let path = Path::new("tests/fixtures/main.rs.gz");
+        let file = GzFileAccess::new(path);
+
+        assert_eq!(file.name(), "tests/fixtures/main.rs");
+        assert_eq!(file.extension().unwrap(), "rs");
+
+        let (language, stats) = LanguageType::parse(file, None).unwrap().unwrap();
+        assert_eq!(language, LanguageType::Rust);
+        assert_eq!(stats.code, 3);
+        assert_eq!(stats.comments, 1);
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn tar_archive() {
+        use language::Languages;
+
+        let mut builder = tar::Builder::new(Vec::new());
+
+        for &(path, contents) in &[
+            ("src/main.rs", "// Entry point.\nfn main() {}\n"),
+            ("script.py", "# Script.\nprint(1)\n\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+
+        let archive = builder.into_inner().unwrap();
+        let entries = tar_entries(&mut tar::Archive::new(&archive[..])).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((&entries[0]).name(), "src/main.rs");
+
+        let mut languages = Languages::new();
+        languages.get_statistics_from(&entries, None);
+
+        assert_eq!(languages[&LanguageType::Rust].code, 1);
+        assert_eq!(languages[&LanguageType::Rust].comments, 1);
+        assert_eq!(languages[&LanguageType::Python].blanks, 1);
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn zip_archive() {
+        use std::io::Write;
+        use language::Languages;
+        use zip::write::{FileOptions, ZipWriter};
+
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        writer.add_directory("src", FileOptions::default()).unwrap();
+
+        for &(path, contents) in &[
+            ("src/main.rs", "// Entry point.\nfn main() {}\n"),
+            ("script.py", "# Script.\nprint(1)\n\n"),
+        ] {
+            writer.start_file(path, FileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+
+        let archive = writer.finish().unwrap().into_inner();
+        let mut archive = zip::ZipArchive::new(io::Cursor::new(archive)).unwrap();
+        let entries = zip_entries(&mut archive).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((&entries[0]).name(), "src/main.rs");
+
+        let mut languages = Languages::new();
+        languages.get_statistics_from(&entries, None);
+
+        assert_eq!(languages[&LanguageType::Rust].code, 1);
+        assert_eq!(languages[&LanguageType::Rust].comments, 1);
+        assert_eq!(languages[&LanguageType::Python].blanks, 1);
+    }
 }
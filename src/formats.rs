@@ -0,0 +1,145 @@
+use std::io;
+
+#[cfg(feature = "msgpack")]
+use rmp_serde;
+#[cfg(feature = "cbor")]
+use serde_cbor;
+
+use language::Languages;
+
+/// Serializes the non-empty languages as CBOR. Custom languages aren't
+/// included.
+///
+/// ```
+/// # use tokei::*;
+/// let mut languages = Languages::new();
+/// languages.insert(LanguageType::Rust, Language { code: 10, lines: 10, ..Language::new() });
+///
+/// let bytes = to_cbor(&languages).unwrap();
+/// let languages = from_cbor(&bytes).unwrap();
+/// assert_eq!(languages[&LanguageType::Rust].code, 10);
+/// ```
+#[cfg(feature = "cbor")]
+pub fn to_cbor(languages: &Languages) -> io::Result<Vec<u8>> {
+    serde_cbor::to_vec(languages).map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// Deserializes languages from the output of `to_cbor`.
+#[cfg(feature = "cbor")]
+pub fn from_cbor(bytes: &[u8]) -> io::Result<Languages> {
+    serde_cbor::from_slice(bytes).map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// Serializes the non-empty languages as MessagePack. Custom languages
+/// aren't included. Structs are written as maps, so that fields added in
+/// later versions can be left out.
+///
+/// ```
+/// # use tokei::*;
+/// let mut languages = Languages::new();
+/// languages.insert(LanguageType::Rust, Language { code: 10, lines: 10, ..Language::new() });
+///
+/// let bytes = to_msgpack(&languages).unwrap();
+/// let languages = from_msgpack(&bytes).unwrap();
+/// assert_eq!(languages[&LanguageType::Rust].code, 10);
+/// ```
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack(languages: &Languages) -> io::Result<Vec<u8>> {
+    rmp_serde::to_vec_named(languages).map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// Deserializes languages from the output of `to_msgpack`.
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack(bytes: &[u8]) -> io::Result<Languages> {
+    rmp_serde::from_slice(bytes).map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+    use language::{Language, LanguageType};
+    use stats::Stats;
+
+    fn languages() -> Languages {
+        let mut rust = Language::new();
+        rust.add_stat(Stats { code: 2, comments: 1, lines: 3, ..Stats::new("main.rs".to_owned()) });
+        rust.add_stat(Stats { code: 1, lines: 1, ..Stats::new("lib.rs".to_owned()) });
+        rust.total();
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, Language::new());
+        languages
+    }
+
+    fn assert_round_trip(decoded: &Languages) {
+        // Empty languages aren't serialized.
+        let mut expected = languages();
+        expected.remove(&LanguageType::C);
+
+        assert_eq!(**decoded, *expected);
+    }
+
+    /// Languages serialized as a map with a repeated key, like the output of
+    /// several runs concatenated.
+    struct Repeated(Vec<(LanguageType, Language)>);
+
+    impl Serialize for Repeated {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+
+            for (language_type, language) in &self.0 {
+                map.serialize_entry(language_type, language)?;
+            }
+
+            map.end()
+        }
+    }
+
+    fn build_rs() -> Stats {
+        Stats { code: 4, lines: 4, ..Stats::new("build.rs".to_owned()) }
+    }
+
+    fn repeated() -> Repeated {
+        let rust = languages().remove(&LanguageType::Rust).unwrap();
+
+        // The totals of this one are left out, so they have to be recomputed.
+        let mut build = Language::new();
+        build.add_stat(build_rs());
+
+        Repeated(vec![(LanguageType::Rust, rust), (LanguageType::Rust, build)])
+    }
+
+    fn assert_merged(decoded: &Languages) {
+        let mut rust = languages().remove(&LanguageType::Rust).unwrap();
+        rust.add_stat(build_rs());
+        rust.total();
+
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[&LanguageType::Rust], rust);
+        assert_eq!(decoded[&LanguageType::Rust].code, 7);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_round_trip() {
+        let bytes = to_cbor(&languages()).unwrap();
+        assert_round_trip(&from_cbor(&bytes).unwrap());
+        assert!(from_cbor(b"\xff").is_err());
+
+        let bytes = serde_cbor::to_vec(&repeated()).unwrap();
+        assert_merged(&from_cbor(&bytes).unwrap());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_round_trip() {
+        let bytes = to_msgpack(&languages()).unwrap();
+        assert_round_trip(&from_msgpack(&bytes).unwrap());
+        assert!(from_msgpack(b"\xc1").is_err());
+
+        let bytes = rmp_serde::to_vec_named(&repeated()).unwrap();
+        assert_merged(&from_msgpack(&bytes).unwrap());
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2015 Aaron Power
+// Use of this source code is governed by the MIT/APACHE2.0 license that can be
+// found in the LICENCE-{APACHE - MIT} file.
+
+use std::borrow::Cow;
+use std::io;
+
+use super::language_type::LanguageType;
+use config::ParseConfig;
+use super::syntax::SyntaxCounter;
+use stats::{LineEnding, Stats};
+use utils::bytes::{self, Bytes};
+use FileAccess;
+
+/// A language which isn't part of tokei's list of languages, registered at
+/// runtime with `Languages::register_custom`.
+///
+/// ```
+/// # use tokei::*;
+/// let language = CustomLanguage {
+///     name: "Acme".to_owned(),
+///     extensions: vec!["acme".to_owned()],
+///     line_comments: vec!["--".to_owned()],
+///     quotes: vec![("\"".to_owned(), "\"".to_owned())],
+///     ..CustomLanguage::default()
+/// };
+///
+/// let stats = parse_custom(&language, "main.acme".into(), b"-- A\nrun \"--\"\n").unwrap();
+/// assert_eq!(stats.comments, 1);
+/// assert_eq!(stats.code, 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CustomLanguage {
+    /// The name of the language, which its statistics are stored under.
+    pub name: String,
+    /// The file extensions of the language, without the leading `.`.
+    pub extensions: Vec<String>,
+    /// The tokens starting a comment that runs to the end of the line.
+    pub line_comments: Vec<String>,
+    /// The start and end tokens of comments spanning multiple lines.
+    pub multi_line_comments: Vec<(String, String)>,
+    /// The start and end tokens of string literals.
+    pub quotes: Vec<(String, String)>,
+}
+
+impl CustomLanguage {
+    /// Whether the file has one of the extensions of this language.
+    pub fn matches<'a, F: FileAccess<'a>>(&self, file_access: F) -> bool {
+        file_access.extension().map_or(false, |extension| {
+            self.extensions.iter().any(|e| e.eq_ignore_ascii_case(&extension))
+        })
+    }
+}
+
+/// Parses the text provided with the comments and quotes of `language`.
+/// Returning `Stats` on success.
+pub fn parse_custom<'a>(language: &CustomLanguage, name: Cow<'a, str>, text: &[u8])
+    -> io::Result<Stats>
+{
+    parse_custom_with_config(language, name, text, &ParseConfig::default())
+}
+
+/// Parses the text provided like `parse_custom`, using the provided
+/// configuration.
+pub(crate) fn parse_custom_with_config<'a>(
+    language: &CustomLanguage,
+    name: Cow<'a, str>,
+    text: &[u8],
+    config: &ParseConfig,
+) -> io::Result<Stats>
+{
+    if bytes::is_binary(text) {
+        return Err(io::Error::new(io::ErrorKind::Other, "binary file"));
+    }
+
+    let (decoded, encoding) = bytes::decode(text)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let decoded = Bytes::new(&decoded);
+
+    let line_comments: Vec<&str> = language.line_comments.iter().map(|c| &**c).collect();
+    let multi_line_comments: Vec<(&str, &str)> = language.multi_line_comments.iter()
+        .map(|(start, end)| (&**start, &**end))
+        .collect();
+    let quotes: Vec<(&str, &str)> = language.quotes.iter()
+        .map(|(start, end)| (&**start, &**end))
+        .collect();
+
+    let mut syntax = SyntaxCounter::custom(&line_comments, &multi_line_comments, &quotes);
+    syntax.todo_markers = &config.todo_markers;
+    syntax.tab_width = config.tab_width;
+    let mut stats = LanguageType::parse_lines_with(
+        &mut syntax,
+        (1..).zip(decoded.lines()),
+        Stats::new(name.to_string()),
+        |_, _| {},
+    );
+
+    stats.bytes = text.len();
+    stats.encoding = Some(encoding);
+    stats.line_ending = LineEnding::detect(decoded.as_bytes());
+    Ok(stats)
+}
@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+use std::io;
+
+use serde_json::{self, Value};
+
+use super::LanguageType;
+use stats::Stats;
+
+/// Parses a Jupyter notebook, counting its code cells as the language of the
+/// notebook's kernel and its markdown cells as comments. The language is
+/// read from `language_info` or the kernelspec, falling back to Python.
+pub(crate) fn parse_notebook<'a>(name: Cow<'a, str>, text: &[u8])
+    -> io::Result<(LanguageType, Stats)>
+{
+    let notebook: Value = serde_json::from_slice(text)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let metadata = &notebook["metadata"];
+    let language = metadata["language_info"]["name"].as_str()
+        .or_else(|| metadata["kernelspec"]["language"].as_str())
+        .and_then(|name| LanguageType::from_info_string(name.as_bytes()))
+        .unwrap_or(LanguageType::Python);
+
+    let mut stats = Stats::new(name.into_owned());
+
+    let cells = notebook["cells"].as_array().map(|c| &c[..]).unwrap_or(&[]);
+
+    for cell in cells {
+        let source = cell_source(&cell["source"]);
+
+        match cell["cell_type"].as_str() {
+            Some("code") => {
+                stats += language.parse_from_str(Cow::from(""), &source);
+            }
+            Some("markdown") => {
+                for line in source.lines() {
+                    if line.trim().is_empty() {
+                        stats.blanks += 1;
+                    } else {
+                        stats.comments += 1;
+                    }
+
+                    stats.lines += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    stats.bytes = text.len();
+    Ok((language, stats))
+}
+
+/// The source of a cell is either a single string, or a list of lines.
+fn cell_source(source: &Value) -> String {
+    match *source {
+        Value::String(ref source) => source.clone(),
+        Value::Array(ref lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn notebook() {
+        let path = Path::new("tests/fixtures/notebook.ipynb");
+        let (language, stats) = LanguageType::parse(path, None).unwrap().unwrap();
+
+        assert_eq!(language, LanguageType::Python);
+        assert_eq!(stats.name, "tests/fixtures/notebook.ipynb");
+        assert_eq!(stats.code, 4);
+        assert_eq!(stats.comments, 3);
+        assert_eq!(stats.blanks, 2);
+        assert_eq!(stats.lines, 9);
+    }
+
+    #[test]
+    fn kernelspec_language() {
+        let text = br#"{"cells": [{"cell_type": "code", "source": "x <- 1"}],
+                        "metadata": {"kernelspec": {"language": "R"}}}"#;
+        let (language, stats) = parse_notebook(Cow::from("r.ipynb"), text).unwrap();
+
+        assert_eq!(language, LanguageType::R);
+        assert_eq!(stats.code, 1);
+    }
+}
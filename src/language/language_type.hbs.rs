@@ -30,6 +30,16 @@ impl LanguageType {
         &[]
     }
 
+    pub(crate) fn blank_doc_line_comments() -> &'static [&'static str] {
+        &[]
+    }
+
+    pub(crate) fn blank_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        &[]
+    }
+
     pub(crate) fn c_allows_nested() -> bool {
         Self::blank_allows_nested()
     }
@@ -48,6 +58,16 @@ impl LanguageType {
         &[("\"", "\"")]
     }
 
+    pub(crate) fn c_doc_line_comments() -> &'static [&'static str] {
+        &["///", "//!"]
+    }
+
+    pub(crate) fn c_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        &[("/**", "*/"), ("/*!", "*/")]
+    }
+
     pub(crate) fn func_allows_nested() -> bool {
         Self::blank_allows_nested()
     }
@@ -66,6 +86,16 @@ impl LanguageType {
         Self::c_quotes()
     }
 
+    pub(crate) fn func_doc_line_comments() -> &'static [&'static str] {
+        Self::blank_doc_line_comments()
+    }
+
+    pub(crate) fn func_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        Self::blank_doc_multi_line_comments()
+    }
+
     pub(crate) fn hash_allows_nested() -> bool {
         Self::blank_allows_nested()
     }
@@ -84,6 +114,16 @@ impl LanguageType {
         Self::blank_quotes()
     }
 
+    pub(crate) fn hash_doc_line_comments() -> &'static [&'static str] {
+        Self::blank_doc_line_comments()
+    }
+
+    pub(crate) fn hash_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        Self::blank_doc_multi_line_comments()
+    }
+
     pub(crate) fn haskell_allows_nested() -> bool {
         true
     }
@@ -102,6 +142,16 @@ impl LanguageType {
         Self::blank_quotes()
     }
 
+    pub(crate) fn haskell_doc_line_comments() -> &'static [&'static str] {
+        &["-- |", "-- ^"]
+    }
+
+    pub(crate) fn haskell_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        &[("{-|", "-}")]
+    }
+
     pub(crate) fn html_allows_nested() -> bool {
         Self::blank_allows_nested()
     }
@@ -120,6 +170,16 @@ impl LanguageType {
         Self::c_quotes()
     }
 
+    pub(crate) fn html_doc_line_comments() -> &'static [&'static str] {
+        Self::blank_doc_line_comments()
+    }
+
+    pub(crate) fn html_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        Self::blank_doc_multi_line_comments()
+    }
+
     pub(crate) fn pro_allows_nested() -> bool {
         Self::blank_allows_nested()
     }
@@ -138,6 +198,16 @@ impl LanguageType {
         Self::c_quotes()
     }
 
+    pub(crate) fn pro_doc_line_comments() -> &'static [&'static str] {
+        Self::blank_doc_line_comments()
+    }
+
+    pub(crate) fn pro_doc_multi_line_comments()
+        -> &'static [(&'static str, &'static str)]
+    {
+        Self::blank_doc_multi_line_comments()
+    }
+
     /// Returns the display name of a language.
     ///
     /// ```
@@ -159,6 +229,26 @@ impl LanguageType {
         }
     }
 
+    /// Returns the lowercase synonyms of a language's name, which are
+    /// accepted by `from_str` besides the name and extensions.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// assert_eq!(LanguageType::Go.aliases(), &["golang"]);
+    /// assert_eq!("golang".parse::<LanguageType>(), Ok(LanguageType::Go));
+    /// ```
+    pub fn aliases(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.aliases}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
     pub(crate) fn is_blank(self) -> bool {
         match self {
             {{#each languages}}
@@ -170,6 +260,23 @@ impl LanguageType {
         }
     }
 
+    /// Whether the language is prose, like documentation, rather than
+    /// source code.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert!(LanguageType::Markdown.is_literate());
+    /// assert!(!LanguageType::Rust.is_literate());
+    /// ```
+    pub fn is_literate(self) -> bool {
+        [
+            {{~#each languages}}
+                {{~#if this.literate}}
+                    {{@key}},
+                {{~/if}}
+            {{~/each}}
+        ].contains(&self)
+    }
+
     pub(crate) fn is_fortran(self) -> bool {
         self == LanguageType::FortranModern ||
         self == LanguageType::FortranLegacy
@@ -244,6 +351,157 @@ impl LanguageType {
     }
 
 
+    /// Returns the single line documentation comments of a language.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Rust;
+    /// assert_eq!(lang.doc_line_comments(), &["///", "//!"]);
+    /// ```
+    pub fn doc_line_comments(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} =>
+                    {{#if this.doc_line_comment}}
+                        &[
+                            {{~#each this.doc_line_comment}}
+                                "{{~this}}",
+                            {{~/each}}
+                        ],
+                    {{else}}
+                        {{#if this.base}}
+                            Self::{{this.base}}_doc_line_comments(),
+                        {{else}}
+                            Self::blank_doc_line_comments(),
+                        {{~/if}}
+                    {{~/if}}
+            {{~/each}}
+        }
+    }
+
+    /// Returns the multi line documentation comments of a language.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Rust;
+    /// assert_eq!(lang.doc_multi_line_comments(), &[("/**", "*/"), ("/*!", "*/")]);
+    /// ```
+    pub fn doc_multi_line_comments(self)
+        -> &'static [(&'static str, &'static str)]
+    {
+        match self {
+            {{#each languages}}
+                {{~@key}} =>
+                    {{#if this.doc_multi_line}}
+                        &[
+                            {{~#each this.doc_multi_line}}
+                                (
+                                {{~#each this}}
+                                    "{{~this}}",
+                                {{~/each}}
+                                ),
+                            {{~/each}}
+                        ],
+                    {{else}}
+                        {{#if this.base}}
+                            Self::{{this.base}}_doc_multi_line_comments(),
+                        {{else}}
+                            Self::blank_doc_multi_line_comments(),
+                        {{~/if}}
+                    {{~/if}}
+            {{~/each}}
+        }
+    }
+
+    /// Returns whether `'` starts character literals in the language, like
+    /// `'a'` or `'\''`, rather than strings. A `'` which doesn't start a
+    /// complete character literal, like in Rust's lifetimes or C++'s digit
+    /// separators, is ignored.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert!(LanguageType::Rust.char_literals());
+    /// assert!(!LanguageType::Python.char_literals());
+    /// ```
+    pub fn char_literals(self) -> bool {
+        [
+            {{~#each languages}}
+                {{~#if this.char_literals}}
+                    {{@key}},
+                {{~/if}}
+            {{~/each}}
+        ].contains(&self)
+    }
+
+    /// Returns whether only strings directly prefixed with an identifier,
+    /// like Scala's `s"${name}"`, contain interpolated expressions.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert!(LanguageType::Scala.prefixed_interpolations());
+    /// assert!(!LanguageType::Kotlin.prefixed_interpolations());
+    /// ```
+    pub fn prefixed_interpolations(self) -> bool {
+        [
+            {{~#each languages}}
+                {{~#if this.prefixed_interpolations}}
+                    {{@key}},
+                {{~/if}}
+            {{~/each}}
+        ].contains(&self)
+    }
+
+    /// Returns whether files of a language start inside of its first embedded
+    /// language, like the HTML around the `<?php` tags of PHP files. The
+    /// embedded blocks of these are only opened outside of their quotes and
+    /// comments.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert!(LanguageType::Php.starts_embedded());
+    /// assert!(!LanguageType::Html.starts_embedded());
+    /// ```
+    pub fn starts_embedded(self) -> bool {
+        [
+            {{~#each languages}}
+                {{~#if this.starts_embedded}}
+                    {{@key}},
+                {{~/if}}
+            {{~/each}}
+        ].contains(&self)
+    }
+
+    /// Returns whether a backslash at the end of a line of code continues it
+    /// on the next line, like in C macros or shell commands. The continued
+    /// lines are counted as the kind of the line they continue.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert!(LanguageType::Sh.line_continuation());
+    /// assert!(!LanguageType::Rust.line_continuation());
+    /// ```
+    pub fn line_continuation(self) -> bool {
+        [
+            {{~#each languages}}
+                {{~#if this.line_continuation}}
+                    {{@key}},
+                {{~/if}}
+            {{~/each}}
+        ].contains(&self)
+    }
+
+    /// Returns whether a backslash at the end of a line comment continues
+    /// the comment on the next line too, like in C, where lines are joined
+    /// before comments are removed.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert!(LanguageType::C.continued_comments());
+    /// assert!(!LanguageType::Sh.continued_comments());
+    /// ```
+    pub fn continued_comments(self) -> bool {
+        [
+            {{~#each languages}}
+                {{~#if this.continued_comments}}
+                    {{@key}},
+                {{~/if}}
+            {{~/each}}
+        ].contains(&self)
+    }
+
     /// Returns whether the language allows nested multi line comments.
     /// ```
     /// use tokei::LanguageType;
@@ -293,11 +551,54 @@ impl LanguageType {
         }
     }
 
+    /// Returns the blocks of other languages that can be embedded in a
+    /// language, as pairs of opening and closing tags and the embedded
+    /// language.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Html;
+    /// assert_eq!(lang.embeds()[0], ("<script", "</script>", LanguageType::JavaScript));
+    /// ```
+    pub fn embeds(self) -> &'static [(&'static str, &'static str, LanguageType)]
+    {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.embeds}}
+                        ("{{this.[0]}}", "{{this.[1]}}", {{this.[2]}}),
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
+    /// Returns the patterns which, at the start of a line of code, mark the
+    /// definition of a function. These are used to count `Stats::functions`.
+    ///
+    /// This is only a heuristic: definitions split across lines, or written
+    /// with modifiers that aren't listed are missed, and anything else that
+    /// happens to start the same way is counted.
+    /// ```
+    /// use tokei::LanguageType;
+    /// assert_eq!(LanguageType::Go.function_patterns(), &["func "]);
+    /// ```
+    pub fn function_patterns(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.function_patterns}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
     /// Returns the quotes of a language.
     /// ```
     /// use tokei::LanguageType;
     /// let lang = LanguageType::Rust;
-    /// assert_eq!(lang.quotes(), &[("r#\"", "\"#"), ("#\"", "\"#"), ("\"", "\"")]);
+    /// assert_eq!(lang.quotes(), &[("\"", "\"")]);
     /// ```
     pub fn quotes(self) -> &'static [(&'static str, &'static str)] {
         match self {
@@ -324,88 +625,346 @@ impl LanguageType {
         }
     }
 
-    pub fn from_path<P: AsRef<Path>>(entry: P) -> Option<Self> {
-        Self::from_file_access(entry.as_ref())
+    /// Returns the verbatim quotes of a language. Unlike regular quotes,
+    /// backslashes in these don't escape anything, and a doubled closing
+    /// quote is an escaped quote.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::CSharp;
+    /// assert!(lang.verbatim_quotes().contains(&("@\"", "\"")));
+    /// ```
+    pub fn verbatim_quotes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.verbatim_quotes}}
+                    (
+                        {{~#each this}} "{{this}}", {{~/each}}
+                    ),
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
     }
 
-    /// Construct a language type from a file extension.
-    pub fn from_extension(extension: &str) -> Option<Self> {
-        match extension {
-            {{~#each languages}}
-                {{~#if this.extensions}}
-                    {{~#each this.extensions}}
-                        "{{~this}}" {{~#unless @last}} | {{~/unless}}
+    /// Returns the literal quotes of a language. Nothing inside of these is
+    /// escaped, so they end at their first closing quote.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Xml;
+    /// assert_eq!(lang.literal_quotes(), &[("<![CDATA[", "]]>")]);
+    /// ```
+    pub fn literal_quotes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.literal_quotes}}
+                    (
+                        {{~#each this}} "{{this}}", {{~/each}}
+                    ),
                     {{~/each}}
-                        => Some({{~@key}}),
-                {{~/if}}
+                ],
             {{~/each}}
-            extension => {
-                warn!("Unknown extension: {}", extension);
-                None
-            },
         }
     }
 
-    pub fn from_file_name(file_name: &str) -> Option<Self> {
-        match file_name {
-            {{~#each languages}}
-                {{~#if this.filenames}}
-                    {{~#each this.filenames}}
-                        "{{~this}}" {{~#unless @last}} | {{~/unless}}
+    /// Returns the raw quotes of a language, as the prefix and the quote
+    /// which open them. Any number of `#` can separate the two, and the
+    /// string is only closed by the quote followed by as many `#`. Like
+    /// verbatim quotes, backslashes don't escape anything in these.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Rust;
+    /// assert!(lang.raw_quotes().contains(&("r", "\"")));
+    /// ```
+    pub fn raw_quotes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.raw_quotes}}
+                    (
+                        {{~#each this}} "{{this}}", {{~/each}}
+                    ),
                     {{~/each}}
-                        => Some({{~@key}}),
-                {{~/if}}
+                ],
             {{~/each}}
-            _ => None
         }
     }
 
-    /// Get language from a file path. May open and read the file.
-    ///
-    /// ```no_run
-    /// # use tokei::*;
-    /// let rust = LanguageType::from_path("./main.rs");
-    ///
-    /// assert_eq!(rust, Some(LanguageType::Rust));
+    /// Returns the start and end of the interpolated expressions in the
+    /// strings of a language. Quotes inside of these don't end the string.
     /// ```
-    pub fn from_file_access<'a, F: FileAccess<'a>>(entry: F) -> Option<Self> {
-        if let Some(language) = entry.file_name().and_then(|name| Self::from_file_name(&name)) {
-            return Some(language);
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Kotlin;
+    /// assert_eq!(lang.interpolations(), &[("${", "}")]);
+    /// ```
+    pub fn interpolations(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.interpolations}}
+                    (
+                        {{~#each this}} "{{this}}", {{~/each}}
+                    ),
+                    {{~/each}}
+                ],
+            {{~/each}}
         }
+    }
 
-        if let Some(language) = entry.extension().and_then(|ext| Self::from_extension(&ext)) {
-            return Some(language);
+    /// Returns the operators which open a heredoc in a language, longest
+    /// first. Operators containing `-` or `~` allow the terminating
+    /// delimiter to be indented.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Ruby;
+    /// assert_eq!(lang.heredoc_delimiters(), &["<<~", "<<-", "<<"]);
+    /// ```
+    pub fn heredoc_delimiters(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.heredoc_delimiters}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
+    /// Returns the tokens which start with a line comment but don't start
+    /// one in a language, like PHP's `#[` attributes.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Php;
+    /// assert_eq!(lang.not_line_comments(), &["#["]);
+    /// ```
+    pub fn not_line_comments(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.not_line_comments}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
+    /// Returns the tokens which start a string running to the end of the
+    /// line in a language, like Zig's `\\` multiline string literals.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Zig;
+    /// assert_eq!(lang.line_quotes(), &["\\\\"]);
+    /// ```
+    pub fn line_quotes(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.line_quotes}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
+    /// Returns the file extensions of a language, which are used by
+    /// `from_extension`.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Rust;
+    /// assert_eq!(lang.extensions(), &["rs"]);
+    /// ```
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.extensions}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
+    /// Returns the file names of a language, which are used by
+    /// `from_file_name`.
+    /// ```
+    /// use tokei::LanguageType;
+    /// let lang = LanguageType::Dockerfile;
+    /// assert_eq!(lang.filenames(), &["dockerfile"]);
+    /// ```
+    pub fn filenames(self) -> &'static [&'static str] {
+        match self {
+            {{#each languages}}
+                {{~@key}} => &[
+                    {{~#each this.filenames}}
+                        "{{this}}",
+                    {{~/each}}
+                ],
+            {{~/each}}
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(entry: P) -> Option<Self> {
+        Self::from_file_access(entry.as_ref())
+    }
+
+    /// Construct a language type from a file extension, without the leading
+    /// `.`. The extension is matched case insensitively.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// assert_eq!(LanguageType::from_extension("rs"), Some(LanguageType::Rust));
+    /// assert_eq!(LanguageType::from_extension("RS"), Some(LanguageType::Rust));
+    /// assert_eq!(LanguageType::from_extension("unknown"), None);
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match &*extension.to_lowercase() {
+            {{~#each languages}}
+                {{~#if this.extensions}}
+                    {{~#each this.extensions}}
+                        "{{~this}}" {{~#unless @last}} | {{~/unless}}
+                    {{~/each}}
+                        => Some({{~@key}}),
+                {{~/if}}
+            {{~/each}}
+            extension => {
+                warn!("Unknown extension: {}", extension);
+                None
+            },
+        }
+    }
+
+    /// Construct a language type from the full name of a file, for languages
+    /// like `Dockerfile` which are recognised by their file names. The name
+    /// is matched case insensitively.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let dockerfile = LanguageType::from_file_name("Dockerfile");
+    /// assert_eq!(dockerfile, Some(LanguageType::Dockerfile));
+    /// assert_eq!(LanguageType::from_file_name("main.rs"), None);
+    /// ```
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        match &*file_name.to_lowercase() {
+            {{~#each languages}}
+                {{~#if this.filenames}}
+                    {{~#each this.filenames}}
+                        "{{~this}}" {{~#unless @last}} | {{~/unless}}
+                    {{~/each}}
+                        => Some({{~@key}}),
+                {{~/if}}
+            {{~/each}}
+            _ => None
+        }
+    }
+
+    /// Get language from a file path. May open and read the file.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let rust = LanguageType::from_path("./main.rs");
+    ///
+    /// assert_eq!(rust, Some(LanguageType::Rust));
+    /// ```
+    pub fn from_file_access<'a, F: FileAccess<'a>>(entry: F) -> Option<Self> {
+        if let Some(language) = entry.file_name().and_then(|name| Self::from_file_name(&name)) {
+            return Some(language);
+        }
+
+        if let Some(language) = entry.extension().and_then(|ext| Self::from_extension(&ext)) {
+            return Some(language);
         }
 
         None
     }
 
     /// Try to determine the language type from the content.
+    ///
+    /// The following signals are checked in order, and the first one to match
+    /// wins:
+    ///
+    /// 1. A shebang on the first line (e.g. `#!/bin/bash`).
+    /// 2. A `<?php` opening tag within the first few lines.
+    /// 3. An `<?xml` prologue within the first few lines.
+    /// 4. A JSON document, starting with `{` or `[` and ending with the
+    ///    matching bracket, with all brackets outside of strings balanced.
+    /// 5. A TOML document, containing both a `[section]` header and a
+    ///    `key = value` pair within the first few lines.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let json = LanguageType::from_content(b"{\"key\": [1, 2]}");
+    /// assert_eq!(json, Some(LanguageType::Json));
+    /// ```
     pub fn from_content(text: &[u8]) -> Option<Self> {
         if let Some(language) = get_filetype_from_shebang(text).and_then(Self::from_extension) {
             return Some(language);
         }
 
+        let first_lines = || Bytes::new(text).lines().take(CONTENT_SNIFF_LINES).map(Bytes::trim);
+
+        if first_lines().any(|line| line.starts_with(b"<?php")) {
+            return Some(Php);
+        }
+
+        if first_lines().any(|line| line.starts_with(b"<?xml")) {
+            return Some(Xml);
+        }
+
+        if looks_like_json(text) {
+            return Some(Json);
+        }
+
+        if looks_like_toml(first_lines()) {
+            return Some(Toml);
+        }
+
         None
     }
+
+    /// Try to determine the language type from an Emacs or Vim modeline
+    /// within the first or last few lines of the content. The mode of an
+    /// Emacs modeline (`-*- mode: python -*-` or `-*- python -*-`) and the
+    /// `ft`, `filetype`, `syntax` or `syn` option of a Vim modeline
+    /// (`vim: set ft=ruby :` or `vim: ft=ruby`) are parsed like `from_str`.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let ruby = LanguageType::from_modeline(b"puts 1\n# vim: set ft=ruby :\n");
+    /// assert_eq!(ruby, Some(LanguageType::Ruby));
+    /// ```
+    pub fn from_modeline(text: &[u8]) -> Option<Self> {
+        // The lines of short contents are inspected twice, which is harmless.
+        let head = Bytes::new(text).lines().take(MODELINE_LINES);
+        let tail = Bytes::new(last_lines(text, MODELINE_LINES)).lines();
+
+        head.chain(tail)
+            .filter_map(|line| modeline_mode(&line.to_utf8_lossy()))
+            .filter_map(|mode| language_from_mode(&mode))
+            .next()
+    }
 }
 
 impl FromStr for LanguageType {
     type Err = &'static str;
 
+    /// Parses the name of a language, one of its `aliases` or one of its
+    /// extensions, ignoring case.
     fn from_str(from: &str) -> Result<Self, Self::Err> {
-        match &*from {
-            {{~#each languages}}
-                {{~#if this.name}}
-                    "{{~this.name}}"
-                {{else}}
-                    "{{~@key}}"
-                {{~/if}}
-                    => Ok({{~@key}}),
-            {{~/each}}
-            _ => Err("Language not found, please use `-l` to see all available\
-                     languages."),
-        }
+        let from = from.to_lowercase();
+        let languages = LanguageType::list();
+
+        languages.iter()
+            .find(|language| {
+                language.name().to_lowercase() == from || language.aliases().contains(&&*from)
+            })
+            .or_else(|| languages.iter().find(|language| language.extensions().contains(&&*from)))
+            .cloned()
+            .ok_or("Language not found, please use `-l` to see all available languages.")
     }
 }
 
@@ -428,51 +987,1378 @@ impl<'a> From<&'a LanguageType> for Cow<'a, LanguageType> {
     }
 }
 
+/// Number of lines inspected by `LanguageType::from_content` when looking for
+/// prologues and TOML tables.
+const CONTENT_SNIFF_LINES: usize = 5;
+
+/// Number of lines at the start and at the end of the content inspected by
+/// `LanguageType::from_modeline`, like Vim's default `modelines`.
+const MODELINE_LINES: usize = 5;
+
+/// The last `count` lines of `text`, found by searching backwards from its
+/// end so that the rest of it isn't split into lines.
+fn last_lines(text: &[u8], count: usize) -> &[u8] {
+    // A line ending at the end of the text doesn't start another line.
+    let mut end = text.len();
+
+    if text.ends_with(b"\n") {
+        end -= 1;
+    }
+
+    if text[..end].ends_with(b"\r") {
+        end -= 1;
+    }
+
+    let mut start = 0;
+
+    for _ in 0..count {
+        match memchr::memrchr2(b'\n', b'\r', &text[..end]) {
+            Some(i) => {
+                start = i + 1;
+                end = if text[i] == b'\n' && i > 0 && text[i - 1] == b'\r' { i - 1 } else { i };
+            }
+            None => return text,
+        }
+    }
+
+    &text[start..]
+}
+
+/// The mode set by an Emacs modeline or the file type set by a Vim modeline
+/// on `line`, if any.
+fn modeline_mode(line: &str) -> Option<String> {
+    if let Some(start) = line.find("-*-") {
+        let rest = &line[start + 3..];
+        let variables = &rest[..rest.find("-*-")?];
+
+        if !variables.contains(':') {
+            return Some(variables.trim().to_owned()).filter(|mode| !mode.is_empty());
+        }
+
+        return variables.split(';').find_map(|variable| {
+            let (name, value) = variable.split_once(':')?;
+
+            Some(value.trim().to_owned()).filter(|_| name.trim().eq_ignore_ascii_case("mode"))
+        });
+    }
+
+    // `vi:`, `vim:`, `Vim:` or `ex:` starting a word, followed by the options.
+    let options = line.char_indices()
+        .filter(|&(i, _)| i == 0 || line[..i].ends_with(char::is_whitespace))
+        .find_map(|(i, _)| {
+            ["vi:", "vim:", "Vim:", "ex:"].iter()
+                .find(|prefix| line[i..].starts_with(*prefix))
+                .map(|prefix| &line[i + prefix.len()..])
+        })?;
+
+    options.split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let (name, value) = option.split_once('=')?;
+
+            match name {
+                "ft" | "filetype" | "syntax" | "syn" if !value.is_empty() => Some(value.to_owned()),
+                _ => None,
+            }
+        })
+}
+
+/// The language of an Emacs mode or Vim file type, where it isn't the name
+/// of the language or one of its extensions.
+fn language_from_mode(mode: &str) -> Option<LanguageType> {
+    match &*mode.to_lowercase() {
+        "emacs-lisp" => Some(Elisp),
+        "make" => Some(Makefile),
+        "shell-script" => Some(Sh),
+        mode => mode.parse().ok(),
+    }
+}
+
+fn looks_like_json(text: &[u8]) -> bool {
+    let text = Bytes::new(text).trim().as_bytes();
+
+    match (text.first(), text.last()) {
+        (Some(b'{'), Some(b'}')) => {},
+        (Some(b'['), Some(b']')) => {
+            // Distinguish `[1, 2]` from a TOML or INI style `[section]`.
+            let value = Bytes::new(&text[1..]).trim().as_bytes();
+
+            match value.first() {
+                Some(b'"') | Some(b'{') | Some(b'[') | Some(b']') | Some(b'-') |
+                Some(b't') | Some(b'f') | Some(b'n') => {},
+                Some(c) if c.is_ascii_digit() => {},
+                _ => return false,
+            }
+        },
+        _ => return false,
+    }
+
+    let mut depth = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &c in text {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {},
+            }
+
+            continue;
+        }
+
+        match c {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth.push(c),
+            b'}' | b']' => {
+                let open = if c == b'}' { b'{' } else { b'[' };
+
+                if depth.pop() != Some(open) {
+                    return false;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    !in_string && depth.is_empty()
+}
+
+fn looks_like_toml<'a>(lines: impl Iterator<Item = Bytes<'a>>) -> bool {
+    let mut has_table = false;
+    let mut has_pair = false;
+
+    for line in lines {
+        let line = line.as_bytes();
+
+        if line.starts_with(b"[") && line.ends_with(b"]") && line.len() > 2 {
+            has_table = true;
+        } else if let Some(index) = line.iter().position(|&c| c == b'=') {
+            let key = Bytes::new(&line[..index]).trim().as_bytes();
+
+            has_pair |= !key.is_empty() && key.iter().all(|&c| {
+                c.is_ascii_alphanumeric() || c == b'_' || c == b'-' || c == b'.' || c == b'"'
+            });
+        }
+    }
+
+    has_table && has_pair
+}
+
+/// Returns every file extension known to tokei, together with the language
+/// `LanguageType::from_extension` maps it to.
+///
+/// ```
+/// # use tokei::*;
+/// assert!(extension_table().contains(&("rs", LanguageType::Rust)));
+/// ```
+pub fn extension_table() -> &'static [(&'static str, LanguageType)] {
+    &[
+        {{~#each languages}}
+            {{~#each this.extensions}}
+                ("{{this}}", {{@../key}}),
+            {{~/each}}
+        {{~/each}}
+    ]
+}
+
+/// A fingerprint of the language definitions tokei was built with, which
+/// changes whenever they do. Statistics stored with another fingerprint, like
+/// a saved `StatsCache`, may no longer match what tokei would count.
+///
+/// ```
+/// # use tokei::*;
+/// assert_eq!(definitions_version().len(), 16);
+/// ```
+pub fn definitions_version() -> &'static str {
+    "{{definitions_version}}"
+}
+
 /// This is for getting the file extension from the first line of a file
+///
+/// The interpreter is the basename of the path following `#!`, or when that
+/// is `env`, of the first argument to `env` which isn't a flag (like `-S`) or
+/// a variable assignment. Interpreters with a version, like `python3.11`,
+/// are also recognised without it.
 pub fn get_filetype_from_shebang(text: &[u8]) -> Option<&'static str> {
     let line = match Bytes::new(text).lines().next() {
         Some(line) => line.to_utf8_lossy(),
         _ => return None,
     };
 
-    let mut words = line.split_whitespace();
-    match words.next() {
-        Some("#!/bin/sh") => Some("sh"),
-        Some("#!/bin/csh") => Some("csh"),
-        Some("#!/usr/bin/perl") => Some("pl"),
-        Some("#!/usr/bin/env") => {
-            if let Some(word) = words.next() {
-                match word {
-                    {{~#each languages}}
-                        {{~#if this.env}}
-                            {{~#each this.env}}
-                                "{{~this}}"
-                                {{~#unless @last}}
-                                    |
-                                {{~/unless}}
-                            {{~/each}}
-                                => Some("{{this.extensions.[0]}}"),
-                        {{~/if}}
-                    {{~/each}}
-                    env => {
-                        warn!("Unknown environment: {:?}", env);
-                        None
-                    }
-                }
-            } else {
-                None
+    if !line.starts_with("#!") {
+        return None;
+    }
+
+    let basename = |path: &str| path.rsplit('/').next().unwrap_or(path).to_owned();
+
+    let mut words = line[2..].split_whitespace();
+    let mut interpreter = basename(words.next()?);
+
+    if interpreter == "env" {
+        let mut skip_next = false;
+
+        interpreter = words.find(|word| {
+            if skip_next {
+                skip_next = false;
+                return false;
             }
-        }
+
+            // These flags take the following word as their argument.
+            skip_next = *word == "-u" || *word == "-C";
+            !word.starts_with('-') && !word.contains('=')
+        }).map(basename)?;
+    }
+
+    let unversioned = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    extension_from_interpreter(&interpreter)
+        .or_else(|| extension_from_interpreter(unversioned))
+        .or_else(|| {
+            debug!("Unknown interpreter: {:?}", interpreter);
+            None
+        })
+}
+
+/// The first extension of the language run by `interpreter`.
+fn extension_from_interpreter(interpreter: &str) -> Option<&'static str> {
+    match interpreter {
+        {{~#each languages}}
+            {{~#if this.env}}
+                {{~#each this.env}}
+                    "{{~this}}"
+                    {{~#unless @last}}
+                        |
+                    {{~/unless}}
+                {{~/each}}
+                    => Some("{{this.extensions.[0]}}"),
+            {{~/if}}
+        {{~/each}}
         _ => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    extern crate tempdir;
     use super::*;
+    use self::tempdir::TempDir;
 
     #[test]
     fn rust() {
         assert_eq!(LanguageType::Rust.allows_nested(), true);
     }
+
+    #[test]
+    fn doc_comments() {
+        let stats = LanguageType::Rust.parse_from_str(Cow::from("doc.rs"), "\
+//! Crate docs.
+
+/// Item docs.
+// Plain comment.
+fn main() {} /** not documentation */
+/**
+ * Block docs.
+ */
+/* Plain
+ * block. */
+");
+        assert_eq!(stats.comments, 8);
+        assert_eq!(stats.doc_comments, 5);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn bytes() {
+        let stats = LanguageType::Text
+            .parse_from_bytes(Cow::from("README.txt"), b"# Title\n\nText\n")
+            .unwrap();
+        assert_eq!(stats.bytes, 14);
+
+        // The byte count is taken before the BOM is stripped by decoding.
+        let stats = LanguageType::Rust
+            .parse_from_bytes(Cow::from("main.rs"), b"\xef\xbb\xbf// x\n")
+            .unwrap();
+        assert_eq!(stats.bytes, 8);
+        assert_eq!(stats.comments, 1);
+    }
+
+    #[test]
+    fn utf8_bom() {
+        let text = "\u{feff}// comment\nx\n";
+
+        let stats = LanguageType::Rust.parse_from_str(Cow::from("main.rs"), text);
+        assert_eq!((stats.code, stats.comments), (1, 1));
+        assert_eq!(stats.max_line_length, 10);
+
+        let stats = LanguageType::Rust.parse_from_reader("main.rs".into(), text.as_bytes()).unwrap();
+        assert_eq!((stats.code, stats.comments), (1, 1));
+        assert_eq!(stats.bytes, text.len());
+
+        let text = "\u{feff}#!/usr/bin/env python\n";
+        let stats = LanguageType::Python.parse_from_str(Cow::from("run.py"), text);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn max_line_length() {
+        let text = "fn main() {\n    let x = 1;\n}\n";
+        let stats = LanguageType::Rust.parse_from_str(Cow::from("main.rs"), text);
+        assert_eq!(stats.max_line_length, 14);
+
+        let stats = LanguageType::Text.parse_from_str(Cow::from("README.txt"), text);
+        assert_eq!(stats.max_line_length, 14);
+
+        let stats = LanguageType::Rust.parse_from_str(Cow::from("empty.rs"), "");
+        assert_eq!(stats.max_line_length, 0);
+    }
+
+    #[test]
+    fn from_content() {
+        assert_eq!(LanguageType::from_content(b"#!/bin/bash\necho hi\n"), Some(Bash));
+        assert_eq!(LanguageType::from_content(b"\n<?php\necho 1;\n"), Some(Php));
+        assert_eq!(
+            LanguageType::from_content(b"<?xml version=\"1.0\"?>\n<root/>\n"),
+            Some(Xml)
+        );
+        assert_eq!(LanguageType::from_content(b"[package]\nname = \"tokei\"\n"), Some(Toml));
+        assert_eq!(LanguageType::from_content(b"just some words\n"), None);
+    }
+
+    #[test]
+    fn from_content_shebang() {
+        let shebang = |text: &[u8]| LanguageType::from_content(text);
+
+        assert_eq!(shebang(b"#!/usr/bin/env ruby2.7\n"), Some(Ruby));
+        assert_eq!(shebang(b"#!/usr/bin/ruby2.7 -w\n"), Some(Ruby));
+        assert_eq!(shebang(b"#!/usr/bin/env node\n"), Some(JavaScript));
+        assert_eq!(shebang(b"#!/usr/bin/env -S deno run --allow-net\n"), Some(TypeScript));
+        assert_eq!(shebang(b"#!/usr/bin/env -S python3 -u\n"), Some(Python));
+        assert_eq!(shebang(b"#!/usr/bin/python3.11\n"), Some(Python));
+        assert_eq!(shebang(b"#!/usr/bin/env -u HOME LANG=C perl\n"), Some(Perl));
+        assert_eq!(shebang(b"#!/bin/sh\n"), Some(Sh));
+        assert_eq!(shebang(b"#!/usr/bin/env\n"), None);
+        assert_eq!(shebang(b"#!/usr/bin/env -S unknown1.0\n"), None);
+    }
+
+    #[test]
+    fn from_content_ambiguous() {
+        // JSON stored in a file without a meaningful extension (e.g. `.txt`).
+        assert_eq!(LanguageType::from_content(b"{\n  \"a\": \"b = c\"\n}\n"), Some(Json));
+        assert_eq!(LanguageType::from_content(b"[1, {\"a\": \"]\"}]"), Some(Json));
+        // Unbalanced brackets are not JSON.
+        assert_eq!(LanguageType::from_content(b"{ \"a\": [1, 2 }"), None);
+        // A lone table header without any keys is not enough to be TOML.
+        assert_eq!(LanguageType::from_content(b"[section]\n"), None);
+        // A PHP tag takes precedence over content that otherwise looks like XML.
+        assert_eq!(LanguageType::from_content(b"<?php\n?>\n<?xml version=\"1.0\"?>\n"), Some(Php));
+    }
+
+    #[test]
+    fn parse_with_callback() {
+        let text = b"let s = \"\n// not a comment\n\";\n/*\n\n*/\n";
+        let mut kinds = Vec::new();
+        let stats = LanguageType::Rust
+            .parse_with_callback(Cow::from("main.rs"), text, |n, kind| kinds.push((n, kind)))
+            .unwrap();
+
+        assert_eq!(kinds, vec![
+            (1, LineKind::Code),
+            (2, LineKind::Code),
+            (3, LineKind::Code),
+            (4, LineKind::Comment),
+            (5, LineKind::Blank),
+            (6, LineKind::Comment),
+        ]);
+        assert_eq!(stats.code, 3);
+        assert_eq!(stats.comments, 2);
+        assert_eq!(stats.blanks, 1);
+    }
+
+    #[test]
+    fn embedded_languages() {
+        let text = "\
+<template>
+  <div>{{ message }}</div>
+</template>
+
+<script>
+// Greeting
+export default { data: () => ({ message: 'Hi' }) }
+</script>
+
+<style scoped>
+div { color: red; }
+</style>
+";
+        let stats = LanguageType::Vue.parse_from_str(Cow::from("app.vue"), text);
+        assert_eq!(stats.code, 7);
+        assert_eq!(stats.blanks, 2);
+        assert_eq!(stats.lines, 9);
+
+        let js = &stats.children[&LanguageType::JavaScript];
+        assert_eq!(js.len(), 1);
+        assert_eq!(js[0].code, 1);
+        assert_eq!(js[0].comments, 1);
+
+        let css = &stats.children[&LanguageType::Css];
+        assert_eq!(css[0].code, 1);
+    }
+
+    #[test]
+    fn embedded_languages_single_line() {
+        let text = "<script src=\"app.js\"></script>\n<p>Text</p>\n";
+        let stats = LanguageType::Html.parse_from_str(Cow::from("index.html"), text);
+        assert_eq!(stats.code, 2);
+        assert!(stats.children.is_empty());
+    }
+
+    #[test]
+    fn markdown() {
+        let text = "\
+# Title
+
+Some prose.
+
+```rust
+// Comment
+fn main() {}
+```
+
+    indented code
+
+```
+plain code
+```
+";
+        let stats = LanguageType::Markdown.parse_from_str(Cow::from("README.md"), text);
+        assert_eq!(stats.comments, 2);
+        assert_eq!(stats.code, 6);
+        assert_eq!(stats.blanks, 4);
+
+        let rust = &stats.children[&LanguageType::Rust];
+        assert_eq!(rust[0].code, 1);
+        assert_eq!(rust[0].comments, 1);
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        use std::io::BufReader;
+
+        let text = "/* multi\r\n * line */\r\nlet s = \"\n// string\n\";\n\n// comment\n";
+        // A tiny buffer makes sure state carries over between reads.
+        let reader = BufReader::with_capacity(4, text.as_bytes());
+        let stats = LanguageType::Rust.parse_from_reader(Cow::from("main.rs"), reader).unwrap();
+
+        assert_eq!(stats.comments, 3);
+        assert_eq!(stats.code, 3);
+        assert_eq!(stats.blanks, 1);
+        assert_eq!(stats.bytes, text.len());
+
+        let reader = BufReader::new(&b"\x7fELF\x02\x01\x01\x00\x00"[..]);
+        assert!(LanguageType::Rust.parse_from_reader(Cow::from("main.rs"), reader).is_err());
+    }
+
+    #[test]
+    fn parse_with_config_utf16() {
+        use std::fs;
+
+        let dir = TempDir::new("utf16").unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, b"\xff\xfe/\x00/\x00\n\x00x\x00\n\x00").unwrap();
+
+        let (_, stats) = LanguageType::parse(&*path, None).unwrap().unwrap();
+        assert_eq!(stats.comments, 1);
+        assert_eq!(stats.code, 1);
+
+        let config = ParseConfig {
+            treat_utf16_as_text: false,
+            ..ParseConfig::default()
+        };
+        assert!(LanguageType::parse_with_config(&*path, None, &config).is_err());
+    }
+
+    #[test]
+    fn utf16_fixtures() {
+        use std::fs;
+        use std::path::Path;
+
+        for fixture in &["tests/fixtures/utf16le.cs", "tests/fixtures/utf16be.cs"] {
+            let path = Path::new(fixture);
+            let (language, stats) = LanguageType::parse(path, None).unwrap().unwrap();
+            assert_eq!(language, LanguageType::CSharp);
+            assert_eq!(stats.lines, 5);
+            assert_eq!(stats.code, 3);
+            assert_eq!(stats.comments, 1);
+            assert_eq!(stats.blanks, 1);
+
+            let text = fs::read(path).unwrap();
+            let stats = LanguageType::CSharp.parse_from_bytes(fixture.to_string().into(), &text).unwrap();
+            assert_eq!(stats.code, 3);
+            assert_eq!(stats.comments, 1);
+
+            let read = LanguageType::CSharp.parse_from_reader(fixture.to_string().into(), &text[..]).unwrap();
+            assert_eq!(read, stats);
+        }
+    }
+
+    #[test]
+    fn latin1() {
+        let stats = LanguageType::Rust
+            .parse_from_bytes("main.rs".into(), b"// caf\xe9\nfn main() {}\n")
+            .unwrap();
+        assert_eq!(stats.comments, 1);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn functions() {
+        let rust = "fn main() {}\n\
+                    // fn commented() {}\n\
+                    /*\n\
+                    fn in_comment() {}\n\
+                    */\n\
+                    let s = \"\n\
+                    fn in_string() {}\";\n\
+                    pub fn public() {}\n\
+                    impl Foo {\n    \
+                        fn method(&self) {}\n\
+                    }\n";
+
+        let stats = LanguageType::Rust.parse_from_str("main.rs".into(), rust);
+        assert_eq!(stats.functions, 3);
+
+        let stats = LanguageType::C.parse_from_str("main.c".into(), "int main() {}\n");
+        assert_eq!(stats.functions, 0);
+    }
+
+    #[test]
+    fn heredocs() {
+        let bash = "cat <<EOF\n\
+                    # not a comment\n\
+                    \"unterminated quote\n\
+                    EOF\n\
+                    # comment\n\
+                    cat <<-'END' | grep '#'\n\
+                    \t# indented, not a comment\n\
+                    \tEND\n\
+                    echo $((1 << 2)) # comment\n";
+
+        let stats = LanguageType::Bash.parse_from_str("main.bash".into(), bash);
+        assert_eq!(stats.code, 8);
+        assert_eq!(stats.comments, 1);
+
+        // Here-strings don't open a heredoc.
+        let bash = "grep x <<<word\n# comment\nword\n";
+        let stats = LanguageType::Bash.parse_from_str("main.bash".into(), bash);
+        assert_eq!((stats.code, stats.comments), (2, 1));
+
+        let ruby = "text = <<~SQL.strip\n  \
+                        SELECT '#' FROM \"table\n  \
+                    SQL\n\
+                    # comment\n\
+                    list << item\n\
+                    puts <<EOS\n\
+                    =begin\n\
+                    EOS\n";
+
+        let stats = LanguageType::Ruby.parse_from_str("main.rb".into(), ruby);
+        assert_eq!(stats.code, 7);
+        assert_eq!(stats.comments, 1);
+    }
+
+    #[test]
+    fn stray_null_byte() {
+        let stats = LanguageType::Rust
+            .parse_from_bytes("main.rs".into(), b"// comment\nfn main() {}\x00\n")
+            .unwrap();
+        assert_eq!(stats.comments, 1);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn fortran_fixed_form() {
+        let fortran = "C comment\n\
+                       \tX = 1\n\
+                       \t! tab comment\n\
+                       10    *  continued label\n\
+                       !     bang comment\n";
+
+        let stats = LanguageType::FortranLegacy.parse_from_str("main.f".into(), fortran);
+        assert_eq!(stats.code, 2);
+        assert_eq!(stats.comments, 3);
+    }
+
+    #[test]
+    fn extension_table_matches_from_extension() {
+        for &(extension, language) in extension_table() {
+            assert!(language.extensions().contains(&extension));
+
+            if extension_table().iter().filter(|&&(e, _)| e == extension).count() == 1 {
+                assert_eq!(LanguageType::from_extension(extension), Some(language));
+            }
+        }
+
+        for language in LanguageType::list() {
+            for file_name in language.filenames() {
+                assert_eq!(LanguageType::from_file_name(file_name), Some(language));
+            }
+        }
+    }
+
+    #[test]
+    fn todos() {
+        use file_access::MemoryFile;
+
+        let text = b"// TODO: one FIXME: two\n\
+                     let x = \"TODO\"; // XXX\n\
+                     /* HACK\n\
+                        NOTE */\n\
+                     // TODOS XXXX TODO_LIST XXX\n";
+        let file = MemoryFile { name: "main.rs", contents: text };
+
+        let (_, stats) = LanguageType::parse(file, None).unwrap().unwrap();
+        assert_eq!(stats.todos, 4);
+
+        let config = ParseConfig {
+            todo_markers: vec!["NOTE".to_owned()],
+            ..ParseConfig::default()
+        };
+
+        let (_, stats) = LanguageType::parse_with_config(file, None, &config).unwrap().unwrap();
+        assert_eq!(stats.todos, 1);
+    }
+
+    #[test]
+    fn line_endings() {
+        let lines = |text: &str| LanguageType::Rust.parse_from_str("main.rs".into(), text);
+
+        assert_eq!(lines("").lines, 0);
+        assert_eq!(lines("\n").blanks, 1);
+        assert_eq!(lines("\n").lines, 1);
+        assert_eq!(lines("a\nb").lines, 2);
+        assert_eq!(lines("a\nb\n").lines, 2);
+
+        let stats = lines("// a\r\nb\r\n\r\n");
+        assert_eq!((stats.comments, stats.code, stats.blanks, stats.lines), (1, 1, 1, 3));
+    }
+
+    #[test]
+    fn carriage_returns() {
+        let lf = "/* A\n   B */\nfn main() {} //\nlet s = \"*/\"; /*\n*/\n";
+        let expected = LanguageType::Rust.parse_from_str("main.rs".into(), lf);
+        assert_eq!((expected.code, expected.comments), (2, 3));
+
+        for ending in &["\r\n", "\r"] {
+            let text = lf.replace("\n", ending);
+            let stats = LanguageType::Rust.parse_from_str("main.rs".into(), &text);
+            assert_eq!((stats.code, stats.comments, stats.lines), (2, 3, 5));
+
+            let stats = LanguageType::Rust
+                .parse_from_reader("main.rs".into(), text.as_bytes())
+                .unwrap();
+            assert_eq!((stats.code, stats.comments, stats.lines), (2, 3, 5));
+        }
+    }
+
+    #[test]
+    fn parse_filtered() {
+        use std::fs;
+
+        let text = b"fn main() {}\n\
+                     #[cfg(test)]\n\
+                     mod tests {\n\
+                     \x20   // test\n\
+                     }\n";
+
+        let mut in_tests = false;
+        let stats = LanguageType::Rust.parse_filtered("main.rs".into(), text, |line| {
+            in_tests |= line.starts_with(b"#[cfg(test)]");
+            !in_tests
+        }).unwrap();
+
+        assert_eq!((stats.code, stats.comments, stats.blanks, stats.lines), (1, 0, 0, 1));
+        assert_eq!(stats.bytes, text.len());
+
+        let text = fs::read("tests/fixtures/utf16le.cs").unwrap();
+        let stats = LanguageType::CSharp.parse_filtered("utf16le.cs".into(), &text, |_| true).unwrap();
+        let expected = LanguageType::CSharp.parse_from_bytes("utf16le.cs".into(), &text).unwrap();
+        assert_eq!(stats, expected);
+    }
+
+    #[test]
+    fn python_docstrings() {
+        let quotes = LanguageType::Python.quotes();
+        assert_eq!(&quotes[..2], &[("\"\"\"", "\"\"\""), ("'''", "'''")]);
+
+        let python = "\"\"\"Docstring with \"quotes\"\n\
+                      # not a comment\n\
+                      // nor this\n\
+                      \"\"\"\n\
+                      # comment\n";
+
+        let stats = LanguageType::Python.parse_from_str("main.py".into(), python);
+        assert_eq!(stats.code, 4);
+        assert_eq!(stats.comments, 1);
+    }
+
+    #[test]
+    fn string_interpolation() {
+        let kotlin = "val s = \"${ s.count { it == '\"' } } quotes\"\n\
+                      // comment\n\
+                      val t = \"\"\"\n\
+                      \x20   ${ names.joinToString(\"\\n\") { \"${it.first}: ${it.second}\" } }\n\
+                      \x20   // not a comment\n\
+                      \"\"\"\n\
+                      /* comment */\n\
+                      val u = \"${\n\
+                      \x20   // comment inside the expression\n\
+                      \x20   names.first()\n\
+                      }\"\n";
+
+        let stats = LanguageType::Kotlin.parse_from_str("main.kt".into(), kotlin);
+        assert_eq!(stats.code, 8);
+        assert_eq!(stats.comments, 3);
+
+        // Only Scala strings with an interpolator interpolate.
+        let scala = "val s = s\"${ \"}\" }\"\n\
+                     // comment\n\
+                     val t = \"${\"\n\
+                     // comment\n";
+
+        let stats = LanguageType::Scala.parse_from_str("main.scala".into(), scala);
+        assert_eq!(stats.code, 2);
+        assert_eq!(stats.comments, 2);
+    }
+
+    #[test]
+    fn skip_generated() {
+        use file_access::MemoryFile;
+
+        let text = b"// Code generated by protoc. @generated\nfn main() {}\n";
+        let file = MemoryFile { name: "main.rs", contents: text };
+
+        let (_, stats) = LanguageType::parse(file, None).unwrap().unwrap();
+        assert!(!stats.generated);
+        assert_eq!(stats.code, 1);
+
+        let config = ParseConfig {
+            skip_generated: true,
+            ..ParseConfig::default()
+        };
+
+        let (language, stats) = LanguageType::parse_with_config(file, None, &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(language, LanguageType::Rust);
+        assert!(stats.generated);
+        assert_eq!((stats.lines, stats.code, stats.comments), (0, 0, 0));
+        assert_eq!(stats.bytes, text.len());
+
+        let config = ParseConfig {
+            generated_sniff_lines: 0,
+            ..config
+        };
+
+        let (_, stats) = LanguageType::parse_with_config(file, None, &config).unwrap().unwrap();
+        assert!(!stats.generated);
+    }
+
+    #[test]
+    fn from_extension_ignores_case() {
+        for language in LanguageType::list() {
+            for extension in language.extensions() {
+                let upper = extension.to_uppercase();
+                let expected = LanguageType::from_extension(extension);
+                assert_eq!(LanguageType::from_extension(&upper), expected);
+            }
+
+            for file_name in language.filenames() {
+                assert_eq!(LanguageType::from_file_name(&file_name.to_uppercase()), Some(language));
+            }
+        }
+    }
+
+    #[test]
+    fn disambiguate_headers() {
+        use file_access::MemoryFile;
+
+        let detect = |contents: &'static [u8]| {
+            let file = MemoryFile { name: "main.h", contents };
+            LanguageType::parse(file, None).unwrap().map(|(language, _)| language)
+        };
+
+        assert_eq!(detect(b"@interface Foo : NSObject\n@end\n"), Some(ObjectiveC));
+        assert_eq!(detect(b"@implementation Foo\n@end\n"), Some(ObjectiveC));
+        assert_eq!(detect(b"class Foo {\n};\n"), Some(CppHeader));
+        assert_eq!(detect(b"namespace foo {\n}\n"), Some(CppHeader));
+        assert_eq!(detect(b"template<typename T>\nT id(T t);\n"), Some(CppHeader));
+        assert_eq!(detect(b"// a class of its own\nint main(void);\n"), Some(CHeader));
+
+        // the content is checked before filtering by types.
+        let file = MemoryFile { name: "main.h", contents: b"class Foo;\n" };
+        let parsed = LanguageType::parse(file, Some(&[CppHeader])).unwrap();
+        assert_eq!(parsed.map(|(language, _)| language), Some(CppHeader));
+        assert!(LanguageType::parse(file, Some(&[CHeader])).unwrap().is_none());
+
+        // overrides are never second guessed.
+        let mut config = ParseConfig::default();
+        config.overrides.extensions.insert("h".to_owned(), CHeader);
+        let (language, _) = LanguageType::parse_with_config(file, None, &config).unwrap().unwrap();
+        assert_eq!(language, CHeader);
+    }
+
+    #[test]
+    fn markdown_front_matter() {
+        let text = "---\ntitle: Post # comment\n\ntags: [a]\n---\n# Title\n\n---\nProse.\n";
+        let stats = LanguageType::Markdown.parse_from_str(Cow::from("post.md"), text);
+        assert_eq!((stats.code, stats.comments, stats.blanks), (2, 3, 1));
+
+        let yaml = &stats.children[&LanguageType::Yaml];
+        assert_eq!((yaml[0].code, yaml[0].blanks), (2, 1));
+
+        let text = "+++\ntitle = \"Post\"\n+++\n";
+        let stats = LanguageType::Markdown.parse_from_str(Cow::from("post.md"), text);
+        assert_eq!((stats.code, stats.comments), (2, 0));
+        assert_eq!(stats.children[&LanguageType::Toml][0].code, 1);
+
+        // A horizontal rule which isn't closed, or isn't on the first line,
+        // is not front matter.
+        for &text in &["---\nProse.\n", "\n---\ntitle: Post\n---\n"] {
+            let stats = LanguageType::Markdown.parse_from_str(Cow::from("post.md"), text);
+            assert!(stats.children.is_empty());
+        }
+    }
+
+    #[test]
+    fn nested_comments() {
+        use language::syntax::SyntaxCounter;
+
+        let mut syntax = SyntaxCounter::new(D);
+        let line = b"/+ a /+ b /+ c // d +/ e +/";
+        let mut depths = Vec::new();
+        let mut i = 0;
+
+        while i < line.len() {
+            let window = &line[i..];
+            let skip = syntax.parse_end_of_multi_line(window)
+                .or_else(|| syntax.parse_multi_line_comment(window));
+
+            match skip {
+                Some(skip) => {
+                    depths.push(syntax.comment_depth());
+                    i += skip;
+                }
+                None => {
+                    assert!(!syntax.parse_line_comment(window));
+                    i += 1;
+                }
+            }
+        }
+
+        assert_eq!(depths, [1, 2, 3, 2, 1]);
+
+        let text = "/+ a /+ b /+ c // d +/ e +/\n+/\nint x;\n/* /+ */\nint y;\n";
+        let stats = D.parse_from_str(Cow::from("main.d"), text);
+        assert_eq!((stats.comments, stats.code), (3, 2));
+    }
+
+    #[test]
+    fn count_path() {
+        use std::fs;
+
+        let dir = TempDir::new("count_path").unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(&path, "// A\nfn main() {}\n").unwrap();
+
+        let (language, stats) = super::count_path(&path).unwrap().unwrap();
+        assert_eq!(language, Rust);
+        assert_eq!((stats.code, stats.comments), (1, 1));
+
+        let unknown = dir.path().join("data.unknown");
+        fs::write(&unknown, "text\n").unwrap();
+        assert!(super::count_path(&unknown).unwrap().is_none());
+        assert!(super::count_path(dir.path().join("missing.rs")).is_err());
+    }
+
+    #[test]
+    fn infrastructure_extensions() {
+        assert_eq!(LanguageType::from_extension("tf"), Some(LanguageType::Hcl));
+        assert_eq!(LanguageType::from_extension("tfvars"), Some(LanguageType::Hcl));
+        assert_eq!(LanguageType::from_extension("hcl"), Some(LanguageType::Hcl));
+        assert_eq!(LanguageType::from_extension("graphql"), Some(LanguageType::GraphQL));
+        assert_eq!(LanguageType::from_extension("gql"), Some(LanguageType::GraphQL));
+        assert_eq!(LanguageType::from_extension("proto"), Some(LanguageType::Protobuf));
+    }
+
+    #[test]
+    fn php_embeds_html() {
+        let text = "\
+<?php
+// Fetch the items
+$items = fetch();
+?>
+<ul>
+<?php foreach ($items as $item): ?>
+  <li><?= $item ?></li>
+
+  <!-- An item -->
+<?php endforeach; ?>
+</ul>
+";
+        let stats = LanguageType::Php.parse_from_str(Cow::from("index.php"), text);
+        assert_eq!((stats.code, stats.comments, stats.lines), (5, 1, 6));
+
+        let html = &stats.children[&LanguageType::Html];
+        assert_eq!(html.len(), 3);
+        assert_eq!((html[0].code, html[0].lines), (1, 1));
+        assert_eq!((html[1].code, html[1].comments, html[1].blanks), (1, 1, 1));
+        assert_eq!(html[1].lines, 3);
+
+        // The HTML after the last `?>` is counted too, unless it's empty.
+        assert_eq!(html[2].code, 1);
+        let stats = LanguageType::Php.parse_from_str(Cow::from("end.php"), "<?php\n?>\n");
+        assert!(stats.children.is_empty());
+
+        // Files start as HTML, and `?>` only ends the PHP outside of quotes
+        // and multi line comments.
+        let text = "\
+<!DOCTYPE html>
+<title>Items</title>
+<?php
+$end = '?>';
+/* ?> */
+echo $end; // The end ?>
+<p>Done</p>
+";
+        let stats = LanguageType::Php.parse_from_str(Cow::from("index.php"), text);
+        assert_eq!((stats.code, stats.comments), (3, 1));
+
+        let html = &stats.children[&LanguageType::Html];
+        assert_eq!(html.len(), 2);
+        assert_eq!((html[0].code, html[1].code), (2, 1));
+    }
+
+    #[test]
+    fn xml_cdata() {
+        let text = "\
+<?xml version=\"1.0\"?>
+<doc><![CDATA[
+<!-- not a comment
+]]></doc>
+<!-- <![CDATA[
+-->
+";
+        let stats = LanguageType::Xml.parse_from_str(Cow::from("doc.xml"), text);
+        assert_eq!((stats.code, stats.comments), (4, 2));
+
+        let counts = |language: LanguageType| {
+            let stats = language.parse_from_str(Cow::from("doc"), text);
+            (stats.code, stats.comments)
+        };
+
+        for &language in &[LanguageType::Svg, LanguageType::XSL, LanguageType::MsBuild] {
+            assert_eq!(counts(language), (4, 2));
+        }
+
+        // CDATA sections have no escapes, so `]]>` always ends them.
+        let text = "<doc><![CDATA[ ]]>]]>\n<!-- comment -->\n";
+        let stats = LanguageType::Xml.parse_from_str(Cow::from("doc.xml"), text);
+        assert_eq!((stats.code, stats.comments), (1, 1));
+    }
+
+    #[test]
+    fn raw_strings() {
+        let count = |text: &str| {
+            let stats = LanguageType::Rust.parse_from_str(Cow::from("main.rs"), text);
+            (stats.code, stats.comments)
+        };
+
+        assert_eq!(count("let s = r##\"contains \"# here\"##;\n// Comment\n"), (1, 1));
+
+        // `"#` doesn't end a string opened with two `#`, even on another line.
+        let text = "\
+let s = r##\"
+// not a comment \"#
+/* not a comment either
+\"##;
+// Comment
+";
+        assert_eq!(count(text), (4, 1));
+
+        // Backslashes don't escape the end of raw strings.
+        assert_eq!(count("let s = r\"C:\\\";\n// Comment\n"), (1, 1));
+        assert_eq!(count("let s = br#\"\\\"#;\n// Comment\n"), (1, 1));
+
+        // A prefix ending an identifier doesn't start a raw string, so the
+        // backslash escapes its end.
+        assert_eq!(count("let s = r\"\\\";\n\";\n// Comment\n"), (3, 0));
+        assert_eq!(count("let s = bar\"\\\";\n\";\n// Comment\n"), (2, 1));
+        assert_eq!(count("return\"\\\";\n\";\n// Comment\n"), (2, 1));
+    }
+
+    #[test]
+    fn char_literals() {
+        let count = |language: LanguageType, text: &str| {
+            let stats = language.parse_from_str(Cow::from("main"), text);
+            (stats.code, stats.comments)
+        };
+
+        // Quotes and comments inside of character literals are ignored.
+        let text = "let q = '\"';\n// Comment\nlet c = '/*';\n// Comment\n";
+        assert_eq!(count(LanguageType::Rust, text), (2, 2));
+
+        // Lifetimes don't start a quote, while the string after them does.
+        let text = "let x: &'a str = \"//\";\n// Comment\n";
+        assert_eq!(count(LanguageType::Rust, text), (1, 1));
+        let text = "fn f<'a>(s: &'a str) -> &'static str { \"/*\nin the string */\"; s }\n";
+        assert_eq!(count(LanguageType::Rust, text), (2, 0));
+
+        // Escaped and non-ASCII characters.
+        let text = "char a = '\\'', b = '\\\"', c = '\\u00e9', d = 'é', e = '\"';\n// Comment\n";
+        assert_eq!(count(LanguageType::Cpp, text), (1, 1));
+
+        // Digit separators aren't character literals.
+        let text = "int n = 1'000'000; /* \"\n*/\n";
+        assert_eq!(count(LanguageType::Cpp, text), (1, 1));
+    }
+
+    #[test]
+    fn shebang() {
+        use file_access::MemoryFile;
+
+        let counts = |name, contents, config: &ParseConfig| {
+            let file = MemoryFile { name, contents };
+            let (_, stats) = LanguageType::parse_with_config(file, None, config).unwrap().unwrap();
+            (stats.code, stats.comments)
+        };
+
+        let default = ParseConfig::default();
+        let as_comment = ParseConfig {
+            count_shebang_as_comment: true,
+            ..ParseConfig::default()
+        };
+
+        // A shebang is code by default, whatever the comments of the language.
+        assert_eq!(counts("run.py", b"#!/usr/bin/env python\n", &default), (1, 0));
+        assert_eq!(counts("run.py", b"#!/usr/bin/env python\n", &as_comment), (0, 1));
+        assert_eq!(counts("run.js", b"#!/usr/bin/env node\n", &as_comment), (0, 1));
+
+        let text = b"#!/bin/sh\necho hi # greet\n";
+        assert_eq!(counts("run.sh", text, &default), (2, 0));
+        assert_eq!(counts("run.sh", text, &as_comment), (1, 1));
+
+        // Only the first line can be a shebang, and Rust's inner attributes
+        // aren't one.
+        assert_eq!(counts("run.py", b"x = 1\n#!python\n", &default), (1, 1));
+        assert_eq!(counts("lib.rs", b"#![no_std]\n", &as_comment), (1, 0));
+
+        let stats = LanguageType::Python.parse_from_reader("run.py".into(), &b"#!python\nx\n"[..]);
+        assert_eq!(stats.unwrap().code, 2);
+    }
+
+    #[test]
+    fn max_file_bytes() {
+        use file_access::MemoryFile;
+
+        let config = ParseConfig {
+            max_file_bytes: Some(13),
+            ..ParseConfig::default()
+        };
+
+        let file = MemoryFile { name: "main.rs", contents: b"fn main() {}\n" };
+        let (_, stats) = LanguageType::parse_with_config(file, None, &config).unwrap().unwrap();
+        assert!(!stats.too_large);
+        assert_eq!(stats.code, 1);
+
+        let file = MemoryFile { name: "main.rs", contents: b"fn main() {}\n\n" };
+        let (language, stats) = LanguageType::parse_with_config(file, None, &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(language, LanguageType::Rust);
+        assert!(stats.too_large);
+        assert_eq!((stats.lines, stats.bytes), (0, 0));
+
+        // The language can still be detected from the start of the file.
+        let file = MemoryFile { name: "run", contents: b"#!/usr/bin/python\nx = 1\n" };
+        let (language, stats) = LanguageType::parse_with_config(file, None, &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(language, LanguageType::Python);
+        assert!(stats.too_large);
+
+        // The largest limit doesn't overflow.
+        let config = ParseConfig { max_file_bytes: Some(usize::MAX), ..config };
+        let file = MemoryFile { name: "main.rs", contents: b"fn main() {}\n" };
+        let (_, stats) = LanguageType::parse_with_config(file, None, &config).unwrap().unwrap();
+        assert!(!stats.too_large);
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn from_str_aliases() {
+        assert_eq!("Rust".parse(), Ok(Rust));
+        assert_eq!("rust".parse(), Ok(Rust));
+        assert_eq!("rs".parse(), Ok(Rust));
+        assert_eq!("golang".parse(), Ok(Go));
+        assert_eq!("js".parse(), Ok(JavaScript));
+        assert_eq!("c++".parse(), Ok(Cpp));
+        assert_eq!("CPP".parse(), Ok(Cpp));
+        assert_eq!("c++ header".parse(), Ok(CppHeader));
+        assert!("nonsense".parse::<LanguageType>().is_err());
+
+        // Aliases don't shadow the names or extensions of other languages.
+        for language in LanguageType::list() {
+            for alias in language.aliases() {
+                assert_eq!(alias.to_lowercase(), *alias);
+                assert_eq!(alias.parse(), Ok(language));
+            }
+
+            assert_eq!(language.name().parse(), Ok(language));
+        }
+    }
+
+    #[test]
+    fn excluded_types() {
+        use file_access::MemoryFile;
+
+        let config = ParseConfig {
+            excluded_types: vec![Markdown, Python],
+            ..ParseConfig::default()
+        };
+
+        let readme = MemoryFile { name: "README.md", contents: b"# Title\n" };
+        let script = MemoryFile { name: "run", contents: b"#!/usr/bin/python\nx = 1\n" };
+        let main = MemoryFile { name: "main.rs", contents: b"fn main() {}\n" };
+
+        assert!(LanguageType::parse_with_config(readme, None, &config).unwrap().is_none());
+        assert!(LanguageType::parse_with_config(script, None, &config).unwrap().is_none());
+        assert!(LanguageType::parse_with_config(main, None, &config).unwrap().is_some());
+
+        // Exclusion wins over the allowed types.
+        let types = [Markdown, Rust];
+        let parse = |file| LanguageType::parse_with_config(file, Some(&types), &config).unwrap();
+        assert!(parse(readme).is_none());
+        assert!(parse(main).is_some());
+
+        // Files detected from their contents are filtered too.
+        assert!(LanguageType::parse(script, Some(&[Rust])).unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_range() {
+        let text = b"fn a() {}\r\n/*\r\nfn b() {}\r\n*/\r\n\r\nfn c() {}\r\n";
+        let counts = |ranges: &[(usize, usize)]| {
+            let ranges: Vec<_> = ranges.iter().map(|&(start, end)| start..end).collect();
+            let stats = Rust.parse_range("main.rs".into(), text, &ranges).unwrap();
+            assert_eq!(stats.bytes, text.len());
+            (stats.code, stats.comments, stats.blanks, stats.lines)
+        };
+
+        assert_eq!(counts(&[(0, text.len())]), (2, 3, 1, 6));
+        assert_eq!(counts(&[]), (0, 0, 0, 0));
+        // `fn b() {}` is within the comment opened on the line before.
+        assert_eq!(counts(&[(15, 20)]), (0, 1, 0, 1));
+        // Only the start of a line has to be within a range.
+        assert_eq!(counts(&[(0, 1), (30, 31)]), (1, 0, 1, 2));
+
+        let range = 0..4;
+        let stats = Rust.parse_range("main.rs".into(), b"\xef\xbb\xbf// a\nb\n", &[range]);
+        assert_eq!(stats.unwrap().comments, 1);
+
+        let text = b"<p>\n<script>\n/*\nlet a = 1;\n*/\n</script>\n";
+        let range = 17..30;
+        let stats = Html.parse_range("index.html".into(), text, &[range]).unwrap();
+        assert_eq!((stats.code, stats.comments), (0, 1));
+        assert!(Rust.parse_range("main.rs".into(), b"\x00\x01", &[]).is_err());
+    }
+
+    #[test]
+    fn assembly_dialects() {
+        let count = |text: &str| {
+            let stats = Assembly.parse_from_str(Cow::from("main.s"), text);
+            (stats.code, stats.comments)
+        };
+
+        // NASM and MASM.
+        assert_eq!(count("; exit\nmov eax, 1 ; syscall\n"), (1, 1));
+        // GAS on x86.
+        assert_eq!(count("# exit\nmovl $1, %eax # syscall\n"), (1, 1));
+        // GAS on AArch64.
+        assert_eq!(count("// exit\nmov x8, #93 // syscall\n"), (1, 1));
+        // GAS run through the C preprocessor.
+        assert_eq!(count("/* exit,\n   then return */\nret\n"), (1, 2));
+        // Comment characters within strings.
+        assert_eq!(count(".ascii \"# ; //\"\n"), (1, 0));
+    }
+
+    #[test]
+    fn definitions_version() {
+        let version = super::definitions_version();
+        assert!(version.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_ne!(version, "0000000000000000");
+    }
+
+    #[test]
+    fn line_continuation() {
+        let count = |language: LanguageType, text: &str| {
+            let stats = language.parse_from_str(Cow::from("main"), text);
+            (stats.code, stats.comments)
+        };
+
+        // Every line of a macro is code, even one starting with a comment.
+        let text = "#define MAX(a, b) \\\n    ((a) > (b) ? \\\n    /* a */ (a) : (b))\n";
+        assert_eq!(count(C, text), (3, 0));
+        assert_eq!(count(Rust, "let a = \\\n/* b */ 1;\n"), (1, 1));
+
+        // Line comments are continued too in C, and counted as the line they
+        // continue.
+        assert_eq!(count(C, "// a \\\n\"b\nint c;\n"), (1, 2));
+        assert_eq!(count(C, "int a; // b \\\n// c\n"), (2, 0));
+        // A blank line ends the continuation.
+        assert_eq!(count(C, "#define A \\\n\n// b\n"), (1, 1));
+
+        let text = "docker run \\\n    --rm \\\n    image\n# comment \\\necho hi\n";
+        assert_eq!(count(Sh, text), (4, 1));
+        // An escaped backslash doesn't continue the line.
+        assert_eq!(count(Sh, "echo \\\\\n# comment\n"), (1, 1));
+        // Nor does one within a string, which continues by itself.
+        assert_eq!(count(Python, "s = 'a \\\nb'\n# c\n"), (2, 1));
+    }
+
+    #[test]
+    fn classify_line() {
+        let classify = |language: LanguageType, lines: &[&str]| {
+            let mut syntax = SyntaxCounter::new(language);
+            let kinds: Vec<_> = lines.iter()
+                .map(|line| language.classify_line(&mut syntax, line))
+                .collect();
+            (kinds, syntax.in_quote(), syntax.in_comment())
+        };
+
+        let lines = ["let s = \"/* a\n", "b */\";\r\n", "/* c /* d */\n", "e */ f();"];
+        let kinds = vec![LineKind::Code, LineKind::Code, LineKind::Comment, LineKind::Code];
+        assert_eq!(classify(Rust, &lines), (kinds, false, false));
+
+        // The state is left as it is at the end of the last line.
+        let (_, in_quote, in_comment) = classify(Python, &["x = '''", "# y"]);
+        assert!(in_quote && !in_comment);
+
+        let kinds = vec![LineKind::Code, LineKind::Code];
+        assert_eq!(classify(Text, &["", "# a"]), (kinds, false, false));
+    }
+
+    #[test]
+    fn from_modeline() {
+        let modeline = |text: &str| LanguageType::from_modeline(text.as_bytes());
+
+        assert_eq!(modeline("# -*- mode: python -*-\n"), Some(Python));
+        assert_eq!(modeline("# -*- coding: utf-8; Mode: Ruby -*-\n"), Some(Ruby));
+        assert_eq!(modeline(";; -*- emacs-lisp -*-\n"), Some(Elisp));
+        assert_eq!(modeline("/* vim: set ft=ruby : */\n"), Some(Ruby));
+        assert_eq!(modeline("# vim: ts=4 filetype=make\n"), Some(Makefile));
+        assert_eq!(modeline("# vi:syn=sh\n"), Some(Sh));
+        assert_eq!(modeline("// ex: ft=cpp\n"), Some(Cpp));
+
+        // Only the first and last few lines are inspected.
+        let middle = format!("{}# vim: ft=ruby\n{}", "x\n".repeat(5), "x\n".repeat(5));
+        assert_eq!(modeline(&middle), None);
+        assert_eq!(modeline(&format!("{}# vim: ft=ruby\n", "x\n".repeat(10))), Some(Ruby));
+
+        // Every kind of line ending counts once when looking for the last lines.
+        for ending in &["\r\n", "\r"] {
+            let tail = |n: usize| format!("{}# vim: ft=ruby{}{}", "x\n".repeat(10), ending, "x\r\n".repeat(n));
+            assert_eq!(modeline(&tail(4)), Some(Ruby));
+            assert_eq!(modeline(&tail(5)), None);
+        }
+
+        assert_eq!(modeline("# -*- coding: utf-8 -*-\n"), None);
+        assert_eq!(modeline("# vim: ft=unknown\n"), None);
+        assert_eq!(modeline("# vim is nice, ft=ruby\n"), None);
+    }
+
+    #[test]
+    fn modeline_precedence() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let script = tmp_dir.path().join("configure");
+        write(&script, "#!/bin/sh\n# -*- mode: python -*-\nprint(1)\n").unwrap();
+        let module = tmp_dir.path().join("module.rb");
+        write(&module, "x = 1\n# vim: set ft=python :\n").unwrap();
+
+        let parse = |path: &Path, config: &ParseConfig| {
+            LanguageType::parse_with_config(path, None, config).unwrap().map(|(l, _)| l)
+        };
+
+        let config = ParseConfig::default();
+        assert_eq!(parse(&script, &config), Some(Sh));
+        assert_eq!(parse(&module, &config), Some(Ruby));
+
+        // Modelines take precedence over shebangs and extensions.
+        let config = ParseConfig { detect_modelines: true, ..ParseConfig::default() };
+        assert_eq!(parse(&script, &config), Some(Python));
+        assert_eq!(parse(&module, &config), Some(Python));
+
+        // But not over the overrides.
+        let mut config = config;
+        config.overrides.extensions.insert("rb".to_owned(), Ruby);
+        assert_eq!(parse(&module, &config), Some(Ruby));
+    }
+
+    #[test]
+    fn erb_embeds_ruby() {
+        let text = "\
+<ul>
+<%
+  # Only the visible items
+  items = @items.select(&:visible?)
+%>
+<% items.each do |item| %>
+  <li><%= item.name %></li>
+<% end %>
+</ul>
+";
+        assert_eq!(LanguageType::from_extension("erb"), Some(RubyHtml));
+
+        let stats = RubyHtml.parse_from_str(Cow::from("index.html.erb"), text);
+        assert_eq!((stats.code, stats.lines), (7, 7));
+
+        // Only blocks spanning several lines are counted as Ruby.
+        let ruby = &stats.children[&Ruby];
+        assert_eq!(ruby.len(), 1);
+        assert_eq!((ruby[0].code, ruby[0].comments), (1, 1));
+    }
+
+    #[test]
+    fn php_comments() {
+        let text = "\
+<?php
+# A comment
+// Another comment
+/* A third */
+#[Route('/users', methods: ['GET'])]
+#[Deprecated]
+function users() {} # A trailing comment
+";
+        let stats = Php.parse_from_str(Cow::from("users.php"), text);
+        assert_eq!((stats.code, stats.comments), (4, 3));
+    }
+
+    #[test]
+    fn tab_width() {
+        let text = b"fn main() {\n\tlet x = 1;\n  \t// Mixed\n}\n";
+        let max_line_length = |tab_width| {
+            let config = ParseConfig { tab_width, ..ParseConfig::default() };
+            Rust.parse_from_bytes_with("main.rs".into(), text, &config, |_, _| {})
+                .unwrap()
+                .max_line_length
+        };
+
+        // Tabs expand to the next tab stop, so `  \t` is as wide as `\t`.
+        assert_eq!(max_line_length(1), 11);
+        assert_eq!(max_line_length(4), 14);
+        assert_eq!(max_line_length(8), 18);
+    }
 }
@@ -1,11 +1,14 @@
 use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fmt;
 use std::path::Path;
 use std::io;
+use std::ops::Range;
 use std::str::FromStr;
 
 use self::LanguageType::*;
-use stats::Stats;
+use config::ParseConfig;
+use stats::{IndentCounter, LineEnding, LineKind, Stats};
 
 use super::syntax::SyntaxCounter;
 use utils::bytes::{self, Bytes};
@@ -13,6 +16,9 @@ use FileAccess;
 
 include!(concat!(env!("OUT_DIR"), "/language_type.rs"));
 
+/// An embedded block of another language, and its numbered lines.
+type Block<'a> = (LanguageType, Vec<(usize, Bytes<'a>)>);
+
 impl LanguageType {
     /// Build a language type and statistics from the given file.
     pub fn parse<'a, F>(
@@ -20,46 +26,193 @@ impl LanguageType {
         types: Option<&[LanguageType]>,
     ) -> io::Result<Option<(LanguageType, Stats)>>
         where F: FileAccess<'a>
+    {
+        LanguageType::parse_with_config(file_access, types, &ParseConfig::default())
+    }
+
+    /// Build a language type and statistics from the given file, using the
+    /// provided configuration.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// # use std::path::Path;
+    /// let config = ParseConfig {
+    ///     binary_sniff_bytes: 1024,
+    ///     ..ParseConfig::default()
+    /// };
+    ///
+    /// let result = LanguageType::parse_with_config(Path::new("Program.cs"), None, &config);
+    /// ```
+    pub fn parse_with_config<'a, F>(
+        file_access: F,
+        types: Option<&[LanguageType]>,
+        config: &ParseConfig,
+    ) -> io::Result<Option<(LanguageType, Stats)>>
+        where F: FileAccess<'a>
+    {
+        LanguageType::parse_with_config_and_text(file_access, types, config, |_| {})
+    }
+
+    /// Build a language type and statistics from the given file like
+    /// `parse_with_config`, passing the whole text of the file to `on_text`
+    /// before it's parsed. Files which are too large aren't passed.
+    pub(crate) fn parse_with_config_and_text<'a, F, T>(
+        file_access: F,
+        types: Option<&[LanguageType]>,
+        config: &ParseConfig,
+        mut on_text: T,
+    ) -> io::Result<Option<(LanguageType, Stats)>>
+        where F: FileAccess<'a>,
+              T: FnMut(&[u8]),
     {
         use std::io::Read;
 
-        let is_supported = |language: &LanguageType| {
-            types.map(|t| t.contains(language)).unwrap_or(true)
-        };
+        let is_supported = |language: &LanguageType| config.is_supported(types, *language);
 
-        // language determined from metadata.
-        if let Some(language) = LanguageType::from_file_access(file_access) {
-            if !is_supported(&language) {
+        let overridden = config.overrides.get(file_access);
+
+        // notebooks are counted as the language of their code cells.
+        #[cfg(feature = "jupyter")]
+        {
+            if overridden.is_none() && file_access.extension().map_or(false, |e| e == "ipynb") {
+                let mut text = Vec::new();
+
+                // the language is only known once read, so large ones are Python.
+                if !config.read_to_end(file_access.open()?, &mut text)? {
+                    let stats = too_large_stats(file_access.name(), config);
+                    return Ok(Some((Python, stats)).filter(|_| is_supported(&Python)));
+                }
+
+                on_text(&text);
+                let (language, stats) = super::jupyter::parse_notebook(file_access.name(), &text)?;
+
+                if !is_supported(&language) {
+                    return Ok(None);
+                }
+
+                return Ok(Some((language, stats)));
+            }
+        }
+
+        // language determined from metadata, unless it's overridden.
+        if let Some(language) = overridden.or_else(|| LanguageType::from_file_access(file_access)) {
+            // ambiguous extensions and modelines are only decided once the
+            // content is read.
+            let ambiguous = overridden.is_none() && language.is_ambiguous();
+            let modeline = overridden.is_none() && config.detect_modelines;
+
+            if !ambiguous && !modeline && !is_supported(&language) {
                 return Ok(None);
             }
 
             let mut text = Vec::new();
-            file_access.open()?.read_to_end(&mut text)?;
-            let stats = language.parse_from_bytes(file_access.name(), &text)?;
+
+            if !config.read_to_end(file_access.open()?, &mut text)? {
+                let stats = too_large_stats(file_access.name(), config);
+                return Ok(Some((language, stats)).filter(|_| is_supported(&language)));
+            }
+
+            let from_modeline = if modeline { LanguageType::from_modeline(&text) } else { None };
+
+            let language = match from_modeline {
+                Some(language) => language,
+                None if ambiguous => language.disambiguate(&text),
+                None => language,
+            };
+
+            if !is_supported(&language) {
+                return Ok(None);
+            }
+
+            on_text(&text);
+            let stats = language.parse_from_bytes_with(file_access.name(), &text, config, |_, _| {})?;
             return Ok(Some((language, stats)));
         }
 
-        // need to read a bit of content, read the first 8000 bytes to check if binary.
+        // need to read a bit of content, read the first few bytes to check if binary.
         let mut text = Vec::new();
         let mut reader = file_access.open()?;
-        (&mut reader).take(8000).read_to_end(&mut text)?;
+        (&mut reader).take(config.binary_sniff_bytes as u64).read_to_end(&mut text)?;
 
         // ignore binary files.
-        if bytes::is_binary(&text) {
+        if config.is_binary(&text) {
             return Ok(None);
         }
 
-        reader.read_to_end(&mut text)?;
+        let fits = config.read_to_end(reader, &mut text)?;
 
-        if let Some(language) = LanguageType::from_content(&text) {
-            let text = bytes::decode(&text).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-            let stats = language.parse_from_bytes_checked(file_access.name(), Bytes::new(&text));
+        let from_modeline = if config.detect_modelines {
+            LanguageType::from_modeline(&text)
+        } else {
+            None
+        };
+
+        if let Some(language) = from_modeline.or_else(|| LanguageType::from_content(&text)) {
+            if !is_supported(&language) {
+                return Ok(None);
+            }
+
+            if !fits {
+                return Ok(Some((language, too_large_stats(file_access.name(), config))));
+            }
+
+            on_text(&text);
+
+            let (decoded, encoding) = bytes::decode(&text)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            let mut stats = language.parse_from_bytes_checked_with(
+                file_access.name(),
+                Bytes::new(&decoded),
+                config,
+                |_, _| {},
+            );
+            stats.bytes = text.len();
+            stats.encoding = Some(encoding);
             return Ok(Some((language, stats)));
         }
 
         Ok(None)
     }
 
+    /// Whether the extension of this language is shared with other
+    /// languages, so that files with it are checked by `disambiguate`.
+    fn is_ambiguous(self) -> bool {
+        self == CHeader
+    }
+
+    /// Decides which of the languages sharing an extension the text is
+    /// written in. A `.h` header is Objective-C if it declares an
+    /// `@interface` or `@implementation`, C++ if it has a `class`,
+    /// `namespace` or `template`, and C otherwise.
+    fn disambiguate(self, text: &[u8]) -> Self {
+        if self != CHeader {
+            return self;
+        }
+
+        let lines = || Bytes::new(text).lines().map(Bytes::trim);
+
+        let is_objective_c = |line: Bytes| {
+            line.starts_with(b"@interface") || line.starts_with(b"@implementation")
+        };
+
+        if lines().any(is_objective_c) {
+            return ObjectiveC;
+        }
+
+        let is_cpp = |line: Bytes| {
+            line.starts_with(b"class ")
+                || line.starts_with(b"namespace ")
+                || line.starts_with(b"template<")
+                || line.starts_with(b"template <")
+        };
+
+        if lines().any(is_cpp) {
+            return CppHeader;
+        }
+
+        CHeader
+    }
+
     /// Parses the text provided. Returning `Stats` on success.
     pub fn parse_from_str<'a>(self, name: Cow<'a, str>, text: &str) -> Stats {
         self.parse_from_bytes_checked(name, Bytes::new(text.as_bytes()))
@@ -67,146 +220,1137 @@ impl LanguageType {
 
     /// Parses the text provided. Returning `Stats` on success.
     pub fn parse_from_bytes<'a>(self, name: Cow<'a, str>, text: &[u8]) -> Result<Stats, io::Error> {
-        if bytes::is_binary(&text) {
+        self.parse_with_callback(name, text, |_, _| {})
+    }
+
+    /// Parses the text provided, calling `callback` with the (1-based) line
+    /// number and the `LineKind` of every line as it is classified. Returning
+    /// `Stats` on success. Lines of embedded languages are reported after the
+    /// rest of the file.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut kinds = Vec::new();
+    /// let text = b"/* A\n   B */\n\nfn main() {}\n";
+    /// LanguageType::Rust
+    ///     .parse_with_callback("main.rs".into(), text, |n, kind| kinds.push((n, kind)))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(kinds, vec![
+    ///     (1, LineKind::Comment),
+    ///     (2, LineKind::Comment),
+    ///     (3, LineKind::Blank),
+    ///     (4, LineKind::Code),
+    /// ]);
+    /// ```
+    pub fn parse_with_callback<'a, F>(self, name: Cow<'a, str>, text: &[u8], callback: F)
+        -> Result<Stats, io::Error>
+        where F: FnMut(usize, LineKind)
+    {
+        self.parse_from_bytes_with(name, text, &ParseConfig::default(), callback)
+    }
+
+    /// Classifies a single line of this language, which may end with a line
+    /// ending, given the state left by the previous lines in `syntax`. The
+    /// state is updated for the next line, so that multi line comments and
+    /// strings are followed across lines.
+    ///
+    /// Unlike `parse_with_callback`, lines are classified one at a time, so
+    /// the rules which need the whole file, like those of Markdown or of
+    /// embedded languages, aren't applied.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut syntax = SyntaxCounter::new(LanguageType::Rust);
+    /// let kinds: Vec<_> = ["/* A", "   B */", "", "fn main() {}"].iter()
+    ///     .map(|line| LanguageType::Rust.classify_line(&mut syntax, line))
+    ///     .collect();
+    ///
+    /// assert_eq!(kinds, [LineKind::Comment, LineKind::Comment, LineKind::Blank, LineKind::Code]);
+    /// assert!(!syntax.in_comment());
+    /// ```
+    pub fn classify_line(self, syntax: &mut SyntaxCounter, line: &str) -> LineKind {
+        let line = line.trim_end_matches(&['\n', '\r'][..]);
+
+        if self.is_blank() {
+            return LineKind::Code;
+        }
+
+        Self::parse_line(syntax, Bytes::new(line.as_bytes()), &mut Stats::new(String::new()))
+    }
+
+    /// Parses the text provided using the provided configuration, reporting
+    /// the kind of each line to `callback`.
+    fn parse_from_bytes_with<'a, F>(
+        self,
+        name: Cow<'a, str>,
+        text: &[u8],
+        config: &ParseConfig,
+        callback: F,
+    ) -> Result<Stats, io::Error>
+        where F: FnMut(usize, LineKind)
+    {
+        if config.is_binary(text) {
+            return Err(io::Error::new(io::ErrorKind::Other, "binary file"));
+        }
+
+        let (decoded, encoding) = bytes::decode(text)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let mut stats = self.parse_from_bytes_checked_with(name, Bytes::new(&decoded), config, callback);
+        stats.bytes = text.len();
+        stats.encoding = Some(encoding);
+        Ok(stats)
+    }
+
+    /// Parses the text read from `reader` line by line, without reading the
+    /// whole file into memory. Returning `Stats` on success.
+    ///
+    /// The first chunk buffered by the reader is used to check whether the
+    /// file is binary, and the text is expected to be UTF-8 (or ASCII
+    /// compatible), with an optional byte order mark. Lines are split like
+    /// `parse_from_bytes` does, on `\n`, `\r\n` or a lone `\r`. Text starting
+    /// with a UTF-16 or UTF-32 byte order mark, and languages which need to
+    /// look at the whole file, like Markdown or those with embedded
+    /// languages, are read into memory first.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let text: &[u8] = b"/* A\n   B */\nfn main() {}\n";
+    /// let stats = LanguageType::Rust.parse_from_reader("main.rs".into(), text).unwrap();
+    ///
+    /// assert_eq!(stats.comments, 2);
+    /// assert_eq!(stats.code, 1);
+    /// ```
+    pub fn parse_from_reader<'a, R>(self, name: Cow<'a, str>, mut reader: R) -> io::Result<Stats>
+        where R: io::BufRead
+    {
+        let config = ParseConfig::default();
+        let start = reader.fill_buf()?;
+
+        if config.is_binary(start) {
             return Err(io::Error::new(io::ErrorKind::Other, "binary file"));
         }
 
-        let text = bytes::decode(text).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-        Ok(self.parse_from_bytes_checked(name, Bytes::new(&text)))
+        if bytes::has_wide_bom(start) || self == Markdown || !self.embeds().is_empty() {
+            let mut text = Vec::new();
+            reader.read_to_end(&mut text)?;
+            return self.parse_from_bytes(name, &text);
+        }
+
+        let mut lines = ReadLines::new(reader);
+        let stats = Stats::new(name.to_string());
+        let numbered = (1..).zip(&mut lines);
+
+        let mut stats = if self.is_blank() {
+            Self::parse_blank_lines(numbered, stats, &config, |_, _| {})
+        } else {
+            self.parse_lines(numbered, stats, &config, |_, _| {})
+        };
+
+        if let Some(error) = lines.error {
+            return Err(error);
+        }
+
+        stats.bytes = lines.bytes;
+        stats.line_ending = lines.line_ending;
+        Ok(stats)
+    }
+
+    /// Parses the text piped to the standard input as this language, with
+    /// `name` as the name of the returned `Stats`. The input is streamed like
+    /// with `parse_from_reader`, so binary input is rejected the same way.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// // cat main.rs | my-program
+    /// let stats = LanguageType::Rust.parse_stdin("<stdin>".into()).unwrap();
+    /// println!("{} lines of code", stats.code);
+    /// ```
+    pub fn parse_stdin<'a>(self, name: Cow<'a, str>) -> io::Result<Stats> {
+        let stdin = io::stdin();
+        let lock = stdin.lock();
+        self.parse_from_reader(name, lock)
     }
 
     /// Parse from a known good (UTF-8) sequence of bytes.
     fn parse_from_bytes_checked<'a>(self, name: Cow<'a, str>, text: Bytes) -> Stats {
-        let lines = text.lines();
+        self.parse_from_bytes_checked_with(name, text, &ParseConfig::default(), |_, _| {})
+    }
+
+    /// Parse from a known good (UTF-8) sequence of bytes, reporting the kind of
+    /// each line to `callback`.
+    fn parse_from_bytes_checked_with<'a, F>(
+        self,
+        name: Cow<'a, str>,
+        text: Bytes,
+        config: &ParseConfig,
+        callback: F,
+    ) -> Stats
+        where F: FnMut(usize, LineKind)
+    {
+        self.parse_filtered_checked(name, text, config, |_| true, callback)
+    }
+
+    /// Parse from a known good (UTF-8) sequence of bytes, skipping the lines
+    /// for which `keep` returns `false` and reporting the kind of every other
+    /// line to `callback`.
+    fn parse_filtered_checked<'a, K, F>(
+        self,
+        name: Cow<'a, str>,
+        text: Bytes,
+        config: &ParseConfig,
+        mut keep: K,
+        callback: F,
+    ) -> Stats
+        where K: FnMut(&Bytes) -> bool,
+              F: FnMut(usize, LineKind)
+    {
         let mut stats = Stats::new(name.to_string());
+        stats.bytes = text.len();
+        stats.line_ending = LineEnding::detect(text.as_bytes());
+        // A BOM would otherwise hide the syntax at the start of the first line.
+        let text = Bytes::new(bytes::strip_utf8_bom(text.as_bytes()));
+
+        if config.is_skipped_generated(text) {
+            stats.generated = true;
+            return stats;
+        }
+
+        let lines = (1..).zip(text.lines()).filter(|(_, line)| keep(line));
+        self.parse_numbered_lines(lines, stats, config, callback)
+    }
+
+    /// Parses the text provided, skipping the lines for which `keep` returns
+    /// `false` before they're classified. The skipped lines aren't included
+    /// in any of the counts of the returned `Stats`, except for `bytes`,
+    /// which is the size of the whole text. Returning `Stats` on success.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let text = b"fn main() {}\n// SKIP\nfn skipped() {}\n\n";
+    /// let stats = LanguageType::Rust
+    ///     .parse_filtered("main.rs".into(), text, |line| !line.as_bytes().ends_with(b"skipped() {}"))
+    ///     .unwrap();
+    ///
+    /// assert_eq!((stats.code, stats.comments, stats.blanks), (1, 1, 1));
+    /// assert_eq!(stats.lines, 3);
+    /// ```
+    pub fn parse_filtered<'a, F>(self, name: Cow<'a, str>, text: &[u8], keep: F)
+        -> Result<Stats, io::Error>
+        where F: FnMut(&Bytes) -> bool
+    {
+        self.parse_filtered_with_config(name, text, &ParseConfig::default(), keep)
+    }
+
+    /// Parses the text provided like `parse_filtered`, using the provided
+    /// configuration. Returning `Stats` on success.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let text = b"// TODO\r\n// REVIEW\r\nfn main() {}\r\n";
+    /// let config = ParseConfig { todo_markers: vec!["REVIEW".to_owned()], ..ParseConfig::default() };
+    /// let stats = LanguageType::Rust
+    ///     .parse_filtered_with_config("main.rs".into(), text, &config, |line| !line.contains(b"TODO"))
+    ///     .unwrap();
+    ///
+    /// assert_eq!((stats.code, stats.comments, stats.todos), (1, 1, 1));
+    /// assert_eq!(stats.line_ending, Some(LineEnding::Crlf));
+    /// ```
+    pub fn parse_filtered_with_config<'a, F>(
+        self,
+        name: Cow<'a, str>,
+        text: &[u8],
+        config: &ParseConfig,
+        keep: F,
+    ) -> Result<Stats, io::Error>
+        where F: FnMut(&Bytes) -> bool
+    {
+        if config.is_binary(text) {
+            return Err(io::Error::new(io::ErrorKind::Other, "binary file"));
+        }
+
+        let (decoded, encoding) = bytes::decode(text)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let mut stats = self.parse_filtered_checked(name, Bytes::new(&decoded), config, keep, |_, _| {});
+        stats.bytes = text.len();
+        stats.encoding = Some(encoding);
+        Ok(stats)
+    }
+
+    /// Parses the text provided, only counting the lines which start within
+    /// one of the byte `ranges` of the text, like the hunks of a diff. The
+    /// whole text is still parsed, so that lines within a multi-line comment
+    /// or string which starts before a range are classified correctly.
+    ///
+    /// Only the blanks, code, comments, lines and `max_line_length` of the
+    /// returned `Stats` are counted, with the lines of embedded languages
+    /// counted as `self`, while `bytes` is the size of the whole text. The
+    /// text is expected to be UTF-8 (or ASCII compatible), so that the
+    /// offsets of its lines are those of `text`. Returning `Stats` on
+    /// success.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let text = b"/* A\n   B */\nfn main() {}\n";
+    /// // The second and third lines.
+    /// let stats = LanguageType::Rust.parse_range("main.rs".into(), text, &[5..27]).unwrap();
+    ///
+    /// assert_eq!((stats.comments, stats.code, stats.lines), (1, 1, 2));
+    /// ```
+    pub fn parse_range<'a>(self, name: Cow<'a, str>, text: &[u8], ranges: &[Range<usize>])
+        -> Result<Stats, io::Error>
+    {
+        if bytes::is_binary(text) {
+            return Err(io::Error::new(io::ErrorKind::Other, "binary file"));
+        }
+
+        let start = text.len() - bytes::strip_utf8_bom(text).len();
+        let lines: Vec<_> = Bytes::new(&text[start..]).lines()
+            .map(|line| (line.as_bytes().as_ptr() as usize - text.as_ptr() as usize, line))
+            .collect();
+        let numbered = (1..).zip(lines.iter().map(|&(_, line)| line));
 
+        let mut stats = Stats::new(name.to_string());
+        stats.bytes = text.len();
+        let config = ParseConfig::default();
+
+        self.parse_numbered_lines(numbered, Stats::new(String::new()), &config, |number, kind| {
+            let (offset, line) = lines[number - 1];
+
+            if !ranges.iter().any(|range| range.contains(&offset)) {
+                return;
+            }
+
+            match kind {
+                LineKind::Code => stats.code += 1,
+                LineKind::Comment => stats.comments += 1,
+                LineKind::Blank => stats.blanks += 1,
+            }
+
+            stats.lines += 1;
+            stats.max_line_length = stats.max_line_length.max(line.len());
+        });
+
+        Ok(stats)
+    }
+
+    /// Parses the numbered lines with the rules of this language, reporting
+    /// the kind of each line to `callback`.
+    fn parse_numbered_lines<'a, F>(
+        self,
+        lines: impl IntoIterator<Item=(usize, Bytes<'a>)>,
+        mut stats: Stats,
+        config: &ParseConfig,
+        mut callback: F,
+    ) -> Stats
+        where F: FnMut(usize, LineKind)
+    {
         if self.is_blank() {
-            let count = lines.count();
-            stats.lines = count;
-            stats.code = count;
-            stats
+            Self::parse_blank_lines(lines, stats, config, callback)
+        } else if self == Markdown {
+            self.parse_markdown(lines, stats, config, callback)
+        } else if !self.embeds().is_empty() {
+            self.parse_embedded(lines, stats, config, callback)
         } else {
-            self.parse_lines(lines, stats)
+            self.parse_lines(lines, stats, config, callback)
+        }
+    }
+
+    /// Counts every one of the lines as code, for languages without any
+    /// syntax.
+    fn parse_blank_lines<L, F>(
+        lines: impl IntoIterator<Item=(usize, L)>,
+        mut stats: Stats,
+        config: &ParseConfig,
+        mut callback: F,
+    ) -> Stats
+        where L: AsRef<[u8]>,
+              F: FnMut(usize, LineKind)
+    {
+        for (number, line) in lines {
+            let line = Bytes::new(line.as_ref());
+            stats.lines += 1;
+            stats.max_line_length = stats.max_line_length.max(line.width(config.tab_width));
+            callback(number, LineKind::Code);
+        }
+
+        stats.code = stats.lines;
+        stats
+    }
+
+    /// Splits out blocks of embedded languages (see `LanguageType::embeds`)
+    /// and counts them separately as children of the returned `Stats`. The
+    /// lines containing the opening and closing tags belong to `self`.
+    fn parse_embedded<'a, F>(
+        self,
+        lines: impl IntoIterator<Item=(usize, Bytes<'a>)>,
+        mut stats: Stats,
+        config: &ParseConfig,
+        mut callback: F,
+    ) -> Stats
+        where F: FnMut(usize, LineKind)
+    {
+        let mut host = Vec::new();
+        let mut blocks = Vec::new();
+        let mut current: Option<(&str, LanguageType, Vec<_>)> = None;
+        // Template languages like PHP start in their markup, and are code
+        // whose quotes and comments can contain the opening tag.
+        let mut syntax = None;
+
+        if self.starts_embedded() {
+            current = self.embeds().first()
+                .map(|&(_, close, language)| (close, language, Vec::new()));
+            syntax = Some(SyntaxCounter::new(self));
+        }
+
+        for (number, line) in lines {
+            // A line closing a block can open the next one after the closing
+            // tag, like `<?php endif; ?>` in PHP.
+            let rest = match current.take() {
+                Some((close, language, mut block)) => match line.find(close.as_bytes()) {
+                    Some(index) => {
+                        // Nothing precedes a block closed on the first line,
+                        // like the markup before `<?php`.
+                        if !block.is_empty() {
+                            blocks.push((language, block));
+                        }
+
+                        Bytes::new(&line.as_bytes()[index + close.len()..])
+                    }
+                    None => {
+                        block.push((number, line));
+                        current = Some((close, language, block));
+                        continue;
+                    }
+                },
+                None => line,
+            };
+
+            host.push((number, line));
+            let start = match syntax {
+                Some(ref mut syntax) => self.find_embed_start_in_code(syntax, rest),
+                None => self.find_embed_start(rest),
+            };
+            current = start.map(|(close, language)| (close, language, Vec::new()));
+        }
+
+        // An unterminated block runs until the end of the file, unless it's
+        // empty, like after PHP's `?>` on the last line.
+        if let Some((_, language, block)) = current {
+            if !block.is_empty() {
+                blocks.push((language, block));
+            }
+        }
+
+        stats = self.parse_lines(host, stats, config, &mut callback);
+
+        for (language, block) in blocks {
+            let child = language.parse_lines(
+                block,
+                Stats::new(stats.name.clone()),
+                config,
+                &mut callback,
+            );
+            stats.children.entry(language).or_default().push(child);
+        }
+
+        stats
+    }
+
+    /// Markdown has no comments, so prose is counted as comments and code
+    /// blocks as code. Fenced code blocks with a recognised info string (e.g.
+    /// ` ```rust `) are counted as children in the language of the block,
+    /// while the fences themselves are Markdown code.
+    ///
+    /// Indented code blocks are recognised as lines indented by four spaces
+    /// or a tab following a blank line, which means that indented
+    /// continuations of list items are counted as code too.
+    ///
+    /// Front matter at the very start of the document is counted as a child
+    /// in YAML or TOML, with its delimiters as Markdown code.
+    fn parse_markdown<'a, F>(
+        self,
+        lines: impl IntoIterator<Item=(usize, Bytes<'a>)>,
+        mut stats: Stats,
+        config: &ParseConfig,
+        mut callback: F,
+    ) -> Stats
+        where F: FnMut(usize, LineKind)
+    {
+        let mut blocks = Vec::new();
+        // The opening fence of the current fenced code block, and the
+        // language and lines of the block if the language is known.
+        let mut fence: Option<(&[u8], Option<Block>)> = None;
+        let mut previous_blank = true;
+        let mut in_indented_block = false;
+
+        let lines: Vec<_> = lines.into_iter().collect();
+        let front_matter = markdown_front_matter(&lines);
+        let mut lines = lines.into_iter();
+
+        if let Some((language, length)) = front_matter {
+            let mut block = Vec::new();
+
+            for (i, (number, line)) in lines.by_ref().take(length).enumerate() {
+                stats.max_line_length = stats.max_line_length.max(line.width(config.tab_width));
+
+                if i == 0 || i == length - 1 {
+                    stats.code += 1;
+                    callback(number, LineKind::Code);
+                } else {
+                    block.push((number, line));
+                }
+            }
+
+            blocks.push((language, block));
+        }
+
+        for (number, line) in lines {
+            stats.max_line_length = stats.max_line_length.max(line.width(config.tab_width));
+            let trimmed = line.trim();
+
+            if let Some((open, block)) = fence.take() {
+                let is_closing = trimmed.len() >= open.len() &&
+                    trimmed.as_bytes().iter().all(|&c| c == open[0]);
+
+                if is_closing {
+                    stats.code += 1;
+                    callback(number, LineKind::Code);
+                    blocks.extend(block);
+                } else if let Some((language, mut block_lines)) = block {
+                    block_lines.push((number, line));
+                    fence = Some((open, Some((language, block_lines))));
+                } else {
+                    if trimmed.is_empty() {
+                        stats.blanks += 1;
+                        callback(number, LineKind::Blank);
+                    } else {
+                        stats.code += 1;
+                        callback(number, LineKind::Code);
+                    }
+
+                    fence = Some((open, None));
+                }
+
+                continue;
+            }
+
+            if line.is_whitespace() {
+                stats.blanks += 1;
+                callback(number, LineKind::Blank);
+                previous_blank = true;
+                continue;
+            }
+
+            if let Some((open, info)) = markdown_fence(trimmed.as_bytes()) {
+                stats.code += 1;
+                callback(number, LineKind::Code);
+                let block = LanguageType::from_info_string(info).map(|l| (l, Vec::new()));
+                fence = Some((open, block));
+                in_indented_block = false;
+            } else if (line.starts_with(b"    ") || line.starts_with(b"\t")) &&
+                      (previous_blank || in_indented_block)
+            {
+                stats.code += 1;
+                callback(number, LineKind::Code);
+                in_indented_block = true;
+            } else {
+                stats.comments += 1;
+                callback(number, LineKind::Comment);
+                in_indented_block = false;
+            }
+
+            previous_blank = false;
+        }
+
+        // An unterminated fenced code block runs until the end of the file.
+        if let Some((_, block)) = fence {
+            blocks.extend(block);
+        }
+
+        stats.lines = stats.blanks + stats.code + stats.comments;
+
+        for (language, block) in blocks {
+            let child = language.parse_lines(
+                block,
+                Stats::new(stats.name.clone()),
+                config,
+                &mut callback,
+            );
+            stats.children.entry(language).or_default().push(child);
+        }
+
+        stats
+    }
+
+    /// Find the language of a Markdown code block from its info string, either
+    /// by name (e.g. `rust`) or extension (e.g. `rs`).
+    pub(crate) fn from_info_string(info: &[u8]) -> Option<Self> {
+        let info = String::from_utf8_lossy(info).to_lowercase();
+
+        if info.is_empty() {
+            return None;
+        }
+
+        LanguageType::list()
+            .into_iter()
+            .find(|l| l.name().to_lowercase() == info)
+            .or_else(|| LanguageType::from_extension(&info))
+    }
+
+    /// Checks whether an embedded block is opened on this line, and not closed
+    /// again on the same line. Returns the closing tag and language of the
+    /// block.
+    fn find_embed_start(self, line: Bytes) -> Option<(&'static str, LanguageType)> {
+        for &(open, close, language) in self.embeds() {
+            let index = match line.find(open.as_bytes()) {
+                Some(index) => index + open.len(),
+                None => continue,
+            };
+
+            let rest = Bytes::new(&line.as_bytes()[index..]);
+
+            // Opening tags ending in a tag name, like `<script`, have to match
+            // the whole name, e.g. not `<scripts`, and be complete. Others,
+            // like PHP's `?>`, are complete on their own.
+            if open.ends_with(|c: char| c.is_ascii_alphanumeric()) {
+                match rest.as_bytes().first() {
+                    Some(b'>') => {},
+                    Some(c) if c.is_ascii_whitespace() => {},
+                    _ => continue,
+                }
+
+                if !rest.contains(b">") {
+                    continue;
+                }
+            }
+
+            if !rest.contains(close.as_bytes()) {
+                return Some((close, language));
+            }
+        }
+
+        None
+    }
+
+    /// Checks whether the first embedded block of a template language is
+    /// opened on this line like `find_embed_start`, where the line is code
+    /// and `syntax` tracks its quotes and comments. The opening tag ends a
+    /// line comment, like PHP's `?>` does.
+    fn find_embed_start_in_code(self, syntax: &mut SyntaxCounter, line: Bytes)
+        -> Option<(&'static str, LanguageType)>
+    {
+        let (open, close, language) = *self.embeds().first()?;
+        let mut line = line.as_bytes();
+
+        loop {
+            let index = find_in_code(syntax, line, open.as_bytes())?;
+            let rest = Bytes::new(&line[index + open.len()..]);
+
+            // The block is closed again on the same line, whose rest is code.
+            match rest.find(close.as_bytes()) {
+                Some(index) => line = &rest.as_bytes()[index + close.len()..],
+                None => return Some((close, language)),
+            }
         }
     }
 
     /// Attempts to parse the line as simply as possible if there are no multi
-    /// line comments or quotes. Returns `bool` indicating whether it was
-    /// successful or not.
+    /// line comments or quotes. Returns the `LineKind` of the line if it was
+    /// successful.
     #[inline]
-    fn parse_basic(self, syntax: &SyntaxCounter, line: Bytes, stats: &mut Stats)
-        -> bool
+    fn parse_basic(syntax: &SyntaxCounter, line: Bytes, stats: &mut Stats)
+        -> Option<LineKind>
     {
         if syntax.quote.is_some() ||
            !syntax.stack.is_empty() ||
+           !syntax.interpolation.is_empty() ||
            syntax.important_syntax().any(|s| line.contains(s.as_bytes()))
         {
-            return false;
+            return None;
         }
 
-        if syntax.line_comments.into_iter()
-                               .any(|s| line.as_bytes()
-                                            .starts_with(s.as_bytes()))
+        let kind = if syntax.line_comments.into_iter()
+                                          .any(|s| line.as_bytes()
+                                                       .starts_with(s.as_bytes())) &&
+                      !syntax.is_not_line_comment(line.as_bytes())
         {
             stats.comments += 1;
             trace!("Comment No.{}", stats.comments);
+
+            if syntax.is_doc_comment(line.as_bytes(), false) {
+                stats.doc_comments += 1;
+                trace!("Doc Comment No.{}", stats.doc_comments);
+            }
+
+            LineKind::Comment
         } else {
             stats.code += 1;
             trace!("Code No.{}", stats.code);
-        }
+            LineKind::Code
+        };
 
         trace!("{}", line);
         trace!("^ Skippable.");
 
-        true
+        Some(kind)
     }
 
     #[inline]
-    fn parse_lines<'a>(
+    fn parse_lines<L, F>(
         self,
-        lines: impl IntoIterator<Item=Bytes<'a>>,
-        mut stats: Stats
+        lines: impl IntoIterator<Item=(usize, L)>,
+        mut stats: Stats,
+        config: &ParseConfig,
+        mut callback: F,
     ) -> Stats
+        where L: AsRef<[u8]>,
+              F: FnMut(usize, LineKind)
     {
         let mut syntax = SyntaxCounter::new(self);
+        syntax.todo_markers = &config.todo_markers;
+        syntax.tab_width = config.tab_width;
+        let mut lines = lines.into_iter().peekable();
 
-        for line in lines {
+        let starts_with_shebang = match lines.peek() {
+            Some(&(1, ref line)) => is_shebang(Bytes::new(line.as_ref())),
+            _ => false,
+        };
 
-            if line.utf8_chars_lossy().all(char::is_whitespace) {
-                stats.blanks += 1;
-                trace!("Blank No.{}", stats.blanks);
-                continue;
+        if starts_with_shebang {
+            if let Some((_, line)) = lines.next() {
+                let kind = count_shebang(Bytes::new(line.as_ref()), &mut stats, config);
+                callback(1, kind);
             }
+        }
+
+        Self::parse_lines_with(&mut syntax, lines, stats, callback)
+    }
+
+    /// Parses the lines with the rules of `syntax`, which doesn't have to
+    /// belong to a generated language.
+    #[inline]
+    pub(crate) fn parse_lines_with<L, F>(
+        syntax: &mut SyntaxCounter,
+        lines: impl IntoIterator<Item=(usize, L)>,
+        mut stats: Stats,
+        mut callback: F,
+    ) -> Stats
+        where L: AsRef<[u8]>,
+              F: FnMut(usize, LineKind)
+    {
+        let mut indent = IndentCounter::default();
+        let mut blank_run = 0;
 
-            // FORTRAN has a rule where it only counts as a comment if it's the
-            // first character in the column, so removing starting whitespace
-            // could cause a miscount.
-            let line = if syntax.is_fortran { line } else { line.trim() };
-            let mut ended_with_comments = false;
-            let mut had_multi_line = !syntax.stack.is_empty();
-            let mut skip = 0;
-            macro_rules! skip {
-                ($skip:expr) => {{
-                    skip = $skip - 1;
-                }}
+        for (number, line) in lines {
+            let line = Bytes::new(line.as_ref());
+            let kind = Self::parse_line_counting_todos(syntax, line, &mut stats);
+
+            if kind == LineKind::Blank {
+                blank_run += 1;
+                stats.max_blank_run = stats.max_blank_run.max(blank_run);
+            } else {
+                blank_run = 0;
             }
 
-            if self.parse_basic(&syntax, line, &mut stats) {
-                continue;
+            if kind == LineKind::Code {
+                indent.add(line.as_bytes());
             }
 
+            callback(number, kind);
+        }
 
-            'window: for i in 0..line.len() {
-                if skip != 0 {
-                    skip -= 1;
-                    continue;
-                }
+        stats.lines = stats.blanks + stats.code + stats.comments;
+        stats.indent = indent.style();
+        stats
+    }
 
-                ended_with_comments = false;
-                let line = line.as_bytes();
-                let window = &line[i..];
+    /// Classifies a single line like `parse_line`, counting the todo markers
+    /// of `syntax` if it's a comment.
+    #[inline]
+    fn parse_line_counting_todos(syntax: &mut SyntaxCounter, line: Bytes, stats: &mut Stats)
+        -> LineKind
+    {
+        let kind = Self::parse_line(syntax, line, stats);
 
-                let is_end_of_quote_or_multi_line =
-                    syntax.parse_end_of_quote(window)
-                    .or_else(|| syntax.parse_end_of_multi_line(window));
+        if kind == LineKind::Comment {
+            stats.todos += syntax.count_todos(line.as_bytes());
+        }
 
-                if let Some(skip_amount) = is_end_of_quote_or_multi_line {
-                    ended_with_comments = true;
-                    skip!(skip_amount);
-                    continue;
-                }
+        kind
+    }
 
-                let is_quote_or_multi_line = syntax.parse_quote(window)
-                    .or_else(|| syntax.parse_multi_line_comment(window));
+    /// Classifies a single line, updating `syntax` with any state which
+    /// carries over to the next line and `stats` with the kind of the line.
+    #[inline]
+    fn parse_line(syntax: &mut SyntaxCounter, line: Bytes, stats: &mut Stats) -> LineKind {
+        stats.max_line_length = stats.max_line_length.max(line.width(syntax.tab_width));
+        // A blank line ends a continued line.
+        let continued = syntax.continued.take();
 
-                if let Some(skip_amount) = is_quote_or_multi_line {
-                    skip!(skip_amount);
-                    continue;
-                }
+        if line.is_whitespace() {
+            stats.blanks += 1;
+            trace!("Blank No.{}", stats.blanks);
+            return LineKind::Blank;
+        }
 
-                if syntax.parse_line_comment(window) {
-                    break 'window;
-                }
+        if syntax.heredoc.is_some() {
+            syntax.end_of_heredoc(line.as_bytes());
+            stats.code += 1;
+            trace!("Code No.{}", stats.code);
+            trace!("^ Inside heredoc.");
+            return LineKind::Code;
+        }
 
-            }
+        if syntax.is_fixed_form {
+            return parse_fixed_form_line(line, stats);
+        }
 
-            trace!("{}", line);
+        // FORTRAN has a rule where it only counts as a comment if it's the
+        // first character in the column, so removing starting whitespace
+        // could cause a miscount.
+        let line = if syntax.is_fortran { line } else { line.trim() };
 
-            if ((!syntax.stack.is_empty() || ended_with_comments) && had_multi_line) ||
-                (syntax.start_of_comments().any(|comment| line.starts_with(comment.as_bytes())) &&
-                 syntax.quote.is_none())
-            {
+        // The whole line is part of a line comment continued from the
+        // previous line.
+        if let Some((kind, true)) = continued {
+            if kind == LineKind::Comment {
                 stats.comments += 1;
-                trace!("Comment No.{}", stats.comments);
-                trace!("Was the Comment stack empty?: {}", !had_multi_line);
             } else {
                 stats.code += 1;
-                trace!("Code No.{}", stats.code);
             }
+
+            syntax.continued = syntax.continuation(line.as_bytes(), kind, true);
+            return kind;
         }
 
-        stats.lines = stats.blanks + stats.code + stats.comments;
-        stats
+        let mut ended_with_comments = false;
+        let mut in_line_comment = false;
+        let mut had_multi_line = !syntax.stack.is_empty();
+        let mut skip = 0;
+        macro_rules! skip {
+            ($skip:expr) => {{
+                skip = $skip - 1;
+            }}
+        }
+
+        if syntax.is_function(line.as_bytes()) {
+            stats.functions += 1;
+            trace!("Function No.{}", stats.functions);
+        }
+
+        if continued.is_none() {
+            if let Some(kind) = Self::parse_basic(syntax, line, stats) {
+                let in_line_comment = kind == LineKind::Comment;
+                syntax.continued = syntax.continuation(line.as_bytes(), kind, in_line_comment);
+                return kind;
+            }
+        }
+
+
+        'window: for i in 0..line.len() {
+            if skip != 0 {
+                skip -= 1;
+                continue;
+            }
+
+            ended_with_comments = false;
+            let line = line.as_bytes();
+
+            if !syntax.token_starts[line[i] as usize] {
+                continue;
+            }
+
+            let window = &line[i..];
+
+            // Checked before the end of a quote, which would otherwise skip
+            // a `\\` start as an escaped backslash.
+            if syntax.parse_line_quote(window) {
+                break 'window;
+            }
+
+            let is_end_of_quote_or_multi_line =
+                syntax.parse_end_of_quote(window)
+                .or_else(|| syntax.parse_end_of_multi_line(window));
+
+            if let Some(skip_amount) = is_end_of_quote_or_multi_line {
+                ended_with_comments = true;
+                skip!(skip_amount);
+                continue;
+            }
+
+            let is_interpolation = syntax.parse_interpolation(window)
+                .or_else(|| syntax.parse_end_of_interpolation(window));
+
+            if let Some(skip_amount) = is_interpolation {
+                skip!(skip_amount);
+                continue;
+            }
+
+            let is_quote_or_multi_line = syntax.parse_quote_after(&line[..i], window)
+                .or_else(|| syntax.parse_multi_line_comment(window));
+
+            if let Some(skip_amount) = is_quote_or_multi_line {
+                skip!(skip_amount);
+                continue;
+            }
+
+            if let Some(skip_amount) = syntax.parse_heredoc(&line[..i], window) {
+                skip!(skip_amount);
+                continue;
+            }
+
+            if syntax.parse_line_comment(window) {
+                in_line_comment = true;
+                break 'window;
+            }
+
+        }
+
+        trace!("{}", line);
+
+        let is_comment = match continued {
+            Some((kind, _)) => kind == LineKind::Comment,
+            None => ((!syntax.stack.is_empty() || ended_with_comments) && had_multi_line) ||
+                (syntax.start_of_comments().any(|comment| line.starts_with(comment.as_bytes())) &&
+                 !syntax.is_not_line_comment(line.as_bytes()) &&
+                 syntax.quote.is_none()),
+        };
+
+        let kind = if is_comment {
+            stats.comments += 1;
+            trace!("Comment No.{}", stats.comments);
+            trace!("Was the Comment stack empty?: {}", !had_multi_line);
+
+            if syntax.is_doc_comment(line.as_bytes(), had_multi_line) {
+                stats.doc_comments += 1;
+                trace!("Doc Comment No.{}", stats.doc_comments);
+            }
+
+            LineKind::Comment
+        } else {
+            stats.code += 1;
+            trace!("Code No.{}", stats.code);
+            LineKind::Code
+        };
+
+        // Remember whether a multi line comment left open at the end of
+        // this line is documentation, so its following lines count too.
+        syntax.in_doc_comment = !syntax.stack.is_empty() && if had_multi_line {
+            syntax.in_doc_comment
+        } else {
+            syntax.doc_multi_line_comments.iter()
+                .any(|(s, _)| line.starts_with(s.as_bytes()))
+        };
+
+        syntax.continued = syntax.continuation(line.as_bytes(), kind, in_line_comment);
+        kind
+    }
+}
+
+/// Finds the first `token` in the line which isn't inside of a quote or multi
+/// line comment, updating `syntax` with the state up to it.
+fn find_in_code(syntax: &mut SyntaxCounter, line: &[u8], token: &[u8]) -> Option<usize> {
+    let mut i = 0;
+
+    while i < line.len() {
+        let window = &line[i..];
+
+        if !syntax.in_quote() && !syntax.in_comment() && window.starts_with(token) {
+            return Some(i);
+        }
+
+        let skip = syntax.parse_end_of_quote(window)
+            .or_else(|| syntax.parse_end_of_multi_line(window))
+            .or_else(|| syntax.parse_quote_after(&line[..i], window))
+            .or_else(|| syntax.parse_multi_line_comment(window));
+
+        if let Some(skip) = skip {
+            i += skip;
+            continue;
+        }
+
+        // Nothing in a line comment but the token counts.
+        if syntax.parse_line_comment(window) {
+            return Bytes::new(window).find(token).map(|index| i + index);
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Counts the file at `path`, returning its language and statistics, or
+/// `None` if its language isn't known. Every language is counted, like
+/// passing `None` as the types to `LanguageType::parse`.
+///
+/// ```no_run
+/// # use tokei::*;
+/// if let Some((language, stats)) = count_path("src/main.rs").unwrap() {
+///     println!("{}: {} lines of code", language, stats.code);
+/// }
+/// ```
+pub fn count_path<P: AsRef<Path>>(path: P) -> io::Result<Option<(LanguageType, Stats)>> {
+    LanguageType::parse(path.as_ref(), None)
+}
+
+/// Checks whether the (trimmed) line opens a fenced Markdown code block.
+/// Returns the opening fence and the first word of the info string.
+fn markdown_fence(line: &[u8]) -> Option<(&[u8], &[u8])> {
+    let fence_char = match line.first() {
+        Some(&c) if c == b'`' || c == b'~' => c,
+        _ => return None,
+    };
+
+    let length = line.iter().take_while(|&&c| c == fence_char).count();
+
+    if length < 3 {
+        return None;
+    }
+
+    let info = Bytes::new(&line[length..]).trim().as_bytes();
+    let end = info.iter()
+        .position(|&c| c.is_ascii_whitespace() || c == b',' || c == b'{')
+        .unwrap_or(info.len());
+
+    Some((&line[..length], &info[..end]))
+}
+
+/// Finds the front matter used by static site generators like Jekyll and
+/// Hugo, which has to start on the first line of a Markdown document: YAML
+/// between `---` and `---` or `...` lines, or TOML between `+++` lines.
+/// Returns its language and number of lines, including both delimiters.
+fn markdown_front_matter(lines: &[(usize, Bytes)]) -> Option<(LanguageType, usize)> {
+    fn is_delimiter(line: Bytes, delimiter: &[u8]) -> bool {
+        line.starts_with(delimiter) &&
+            Bytes::new(&line.as_bytes()[delimiter.len()..]).is_whitespace()
+    }
+
+    let first = lines.first()?.1;
+
+    let (language, closing): (LanguageType, &[&[u8]]) = if is_delimiter(first, b"---") {
+        (Yaml, &[b"---", b"..."])
+    } else if is_delimiter(first, b"+++") {
+        (Toml, &[b"+++"])
+    } else {
+        return None;
+    };
+
+    lines[1..].iter()
+        .position(|&(_, line)| closing.iter().any(|delimiter| is_delimiter(line, delimiter)))
+        .map(|end| (language, end + 2))
+}
+
+/// The statistics of a file which is larger than `ParseConfig::max_file_bytes`.
+pub(crate) fn too_large_stats(name: Cow<str>, config: &ParseConfig) -> Stats {
+    warn!("Skipping {}, which is larger than {} bytes.", name, config.max_file_bytes.unwrap_or(0));
+    let mut stats = Stats::new(name.to_string());
+    stats.too_large = true;
+    stats
+}
+
+/// The lines of a reader, which is read one chunk ending in a `\n` at a
+/// time. The bytes read and the line endings found are recorded, and the
+/// lines end at the first error, which is kept.
+struct ReadLines<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    lines: VecDeque<Vec<u8>>,
+    first: bool,
+    bytes: usize,
+    line_ending: Option<LineEnding>,
+    error: Option<io::Error>,
+}
+
+impl<R: io::BufRead> ReadLines<R> {
+    fn new(reader: R) -> Self {
+        ReadLines {
+            reader,
+            buffer: Vec::new(),
+            lines: VecDeque::new(),
+            first: true,
+            bytes: 0,
+            line_ending: None,
+            error: None,
+        }
+    }
+}
+
+impl<R: io::BufRead> Iterator for ReadLines<R> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        while self.lines.is_empty() {
+            self.buffer.clear();
+
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => return None,
+                Ok(read) => self.bytes += read,
+                Err(error) => {
+                    self.error = Some(error);
+                    return None;
+                }
+            }
+
+            let chunk = if self.first { bytes::strip_utf8_bom(&self.buffer) } else { &self.buffer };
+            self.first = false;
+            self.line_ending = LineEnding::combine(self.line_ending, LineEnding::detect(chunk));
+
+            // The chunk ends at a `\n`, but can contain lines ending in a lone `\r`.
+            self.lines.extend(Bytes::new(chunk).lines().map(|line| line.as_bytes().to_vec()));
+        }
+
+        self.lines.pop_front()
     }
 }
 
+/// Whether `line` is a shebang (`#!`), which is only recognised on the first
+/// line of a file. Rust's inner attributes (`#![...]`) aren't shebangs.
+fn is_shebang(line: Bytes) -> bool {
+    line.starts_with(b"#!") && !line.starts_with(b"#![")
+}
+
+/// Counts a shebang as code, or as a comment if `count_shebang_as_comment`
+/// is set, regardless of the comment syntax of the language.
+fn count_shebang(line: Bytes, stats: &mut Stats, config: &ParseConfig) -> LineKind {
+    stats.max_line_length = stats.max_line_length.max(line.width(config.tab_width));
+
+    if config.count_shebang_as_comment {
+        stats.comments += 1;
+        LineKind::Comment
+    } else {
+        stats.code += 1;
+        LineKind::Code
+    }
+}
+
+/// Classifies a line of fixed-form FORTRAN. A `C`, `c`, `*` or `!` in the
+/// first column makes the whole line a comment. Otherwise columns 1-5 hold a
+/// statement label and column 6 marks continuation lines, so the statement
+/// only starts at column 7 (or after a leading tab). Lines whose statement
+/// starts with `!` are comments too.
+fn parse_fixed_form_line(line: Bytes, stats: &mut Stats) -> LineKind {
+    let bytes = line.as_bytes();
+
+    let is_comment = match bytes.first() {
+        Some(b'c') | Some(b'C') | Some(b'*') | Some(b'!') => true,
+        Some(b'\t') => Bytes::new(&bytes[1..]).trim().starts_with(b"!"),
+        _ => {
+            let (label, statement) = bytes.split_at(usize::min(6, bytes.len()));
+
+            Bytes::new(label).trim().is_empty() &&
+                Bytes::new(statement).trim().starts_with(b"!")
+        }
+    };
+
+    if is_comment {
+        stats.comments += 1;
+        trace!("Comment No.{}", stats.comments);
+        LineKind::Comment
+    } else {
+        stats.code += 1;
+        trace!("Code No.{}", stats.code);
+        LineKind::Code
+    }
+}
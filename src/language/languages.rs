@@ -3,14 +3,25 @@
 // found in the LICENCE-{APACHE/MIT} file.
 
 use std::collections::{btree_map, BTreeMap};
+use std::io;
 use std::iter::IntoIterator;
+use std::path::{Component, Path, PathBuf};
 use std::ops::{AddAssign, Deref, DerefMut};
 
 use rayon::prelude::*;
 
 #[cfg(feature = "io")] use serde;
+#[cfg(feature = "io")] use std::fmt;
 
-use super::{Language, LanguageType};
+use super::{CustomLanguage, Language, LanguageType};
+use cache::StatsCache;
+use cocomo::{CocomoEstimate, CocomoParams};
+use config::{ParseConfig, WalkConfig};
+use diff::LanguageDiff;
+use progress::{Progress, Reporter};
+use sort::Sort;
+use stats::Stats;
+use summary::Summary;
 use utils;
 use FileAccess;
 
@@ -18,6 +29,12 @@ use FileAccess;
 #[derive(Debug, Default)]
 pub struct Languages {
     inner: BTreeMap<LanguageType, Language>,
+    pub(crate) custom_languages: Vec<CustomLanguage>,
+    pub(crate) custom: BTreeMap<String, Language>,
+    pub(crate) config: ParseConfig,
+    pub(crate) progress: Reporter,
+    pub(crate) cache: Option<StatsCache>,
+    pub(crate) dedup: bool,
 }
 
 #[cfg(feature = "io")]
@@ -33,19 +50,50 @@ impl serde::Serialize for Languages {
 impl<'de> serde::Deserialize<'de> for Languages {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: serde::Deserializer<'de> {
-            let map = <_>::deserialize(deserializer)?;
+            deserializer.deserialize_map(LanguagesVisitor)
+        }
+}
+
+/// Collects every entry of a map of languages, so that a repeated language
+/// is merged by `Languages::from_previous` instead of replacing the first.
+#[cfg(feature = "io")]
+struct LanguagesVisitor;
+
+#[cfg(feature = "io")]
+impl<'de> serde::de::Visitor<'de> for LanguagesVisitor {
+    type Value = Languages;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of languages")
+    }
 
-            Ok(Self::from_previous(map))
+    fn visit_map<A>(self, mut map: A) -> Result<Languages, A::Error>
+        where A: serde::de::MapAccess<'de>
+    {
+        let mut languages = Vec::new();
+
+        while let Some(entry) = map.next_entry()? {
+            languages.push(entry);
         }
+
+        Ok(Languages::from_previous(languages))
+    }
 }
 
 impl Languages {
+    /// Builds `Languages` from deserialized languages, merging any which are
+    /// repeated. The totals of those with the statistics of their files are
+    /// recomputed from these, so they can't disagree.
     #[cfg(feature = "io")]
-    fn from_previous(map: BTreeMap<LanguageType, Language>) -> Self {
+    fn from_previous(map: Vec<(LanguageType, Language)>) -> Self {
         use std::collections::btree_map::Entry::*;
         let mut _self = Self::new();
 
-        for (name, input_language) in map {
+        for (name, mut input_language) in map {
+            if !input_language.stats.is_empty() {
+                input_language.total();
+            }
+
             match _self.entry(name) {
                 Occupied(mut entry) => {
                     *entry.get_mut() += input_language;
@@ -71,9 +119,104 @@ impl Languages {
                           ignored: Vec<&str>,
                           types: Option<Vec<LanguageType>>)
     {
-        utils::fs::get_all_files(paths, ignored, &mut self.inner, types);
+        utils::fs::get_all_files(paths, ignored, self, types);
 
-        self.inner.par_iter_mut().for_each(|(_, l)| l.total());
+        self.total();
+    }
+
+    /// Get statistics like `get_statistics`, returning the paths of the files
+    /// which couldn't be read together with their errors instead of logging
+    /// them. Files which could be read are still counted.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// let errors = languages.try_get_statistics(&["."], vec![".git", "target"], None);
+    ///
+    /// for (path, error) in errors {
+    ///     eprintln!("{} reading {}", error, path.display());
+    /// }
+    /// ```
+    pub fn try_get_statistics(&mut self,
+                              paths: &[&str],
+                              ignored: Vec<&str>,
+                              types: Option<Vec<LanguageType>>)
+        -> Vec<(PathBuf, io::Error)>
+    {
+        let errors = utils::fs::try_get_all_files(paths, ignored, self, types);
+
+        self.total();
+        errors
+    }
+
+    /// Parses the files under the list of paths provided like
+    /// `try_get_statistics`, calling `callback` with the name, language and
+    /// statistics of each file as soon as it's parsed, instead of adding them
+    /// to these languages. Files are parsed in parallel, so `callback` is
+    /// called from several threads at once, in no particular order.
+    ///
+    /// The settings of these languages, like extension overrides and the
+    /// cache, are still used. Files of custom languages aren't reported, and
+    /// identical files aren't deduplicated.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let languages = Languages::new();
+    /// let errors = languages.for_each_file(&["."], vec![".git", "target"], None,
+    ///     |name, language, stats| println!("{} ({}): {} lines", name, language, stats.lines));
+    /// ```
+    pub fn for_each_file<F>(&self,
+                            paths: &[&str],
+                            ignored: Vec<&str>,
+                            types: Option<Vec<LanguageType>>,
+                            callback: F)
+        -> Vec<(PathBuf, io::Error)>
+        where F: Fn(&str, LanguageType, Stats) + Send + Sync
+    {
+        utils::fs::for_each_file(paths, ignored, self, types, callback)
+    }
+
+    /// Get statistics from the list of paths provided, only counting files
+    /// allowed by the list of globs. Globs prefixed with `!` exclude the files
+    /// they match, any other glob only includes the files it matches. These
+    /// take precedence over `.gitignore` files.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics_with_globs(&["."], &["!*.min.js", "!vendor/**"], None);
+    /// ```
+    pub fn get_statistics_with_globs(&mut self,
+                                     paths: &[&str],
+                                     globs: &[&str],
+                                     types: Option<Vec<LanguageType>>)
+    {
+        self.get_statistics_with_config(paths, globs, types, &WalkConfig::default());
+    }
+
+    /// Get statistics from the list of paths provided, like
+    /// `get_statistics_with_globs`, with `config` controlling how the
+    /// directories are walked.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let config = WalkConfig {
+    ///     git_ignore: false,
+    ///     ..WalkConfig::default()
+    /// };
+    ///
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics_with_config(&["."], &[], None, &config);
+    /// ```
+    pub fn get_statistics_with_config(&mut self,
+                                      paths: &[&str],
+                                      globs: &[&str],
+                                      types: Option<Vec<LanguageType>>,
+                                      config: &WalkConfig)
+    {
+        utils::fs::get_all_files_with_globs(paths, globs, self, types, config);
+
+        self.total();
     }
 
     /// Get statistics from a collection of objects.
@@ -100,8 +243,127 @@ impl Languages {
         where I: IntoIterator<Item = F>,
               F: Send + FileAccess<'a>,
     {
-        utils::fs::get_all_file_accesses(files, &mut self.inner, types);
+        utils::fs::get_all_file_accesses(files, self, types);
+        self.total();
+    }
+
+    /// Get statistics from exactly the files provided, without walking any
+    /// directories or applying ignore files. This is useful when the set of
+    /// files has already been decided, e.g. from the output of
+    /// `git ls-files`.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// # use std::path::PathBuf;
+    /// let mut languages = Languages::new();
+    /// let files = vec![PathBuf::from("src/lib.rs"), PathBuf::from("build.rs")];
+    /// languages.get_statistics_from_paths(files, None);
+    /// ```
+    pub fn get_statistics_from_paths(&mut self,
+                                     paths: impl IntoIterator<Item = PathBuf>,
+                                     types: Option<Vec<LanguageType>>)
+    {
+        let paths: Vec<_> = paths.into_iter().collect();
+        self.get_statistics_from(paths.iter().map(|p| p.as_path()), types);
+    }
+
+    /// Totals up the statistics of every language, including custom ones.
+    fn total(&mut self) {
         self.inner.par_iter_mut().for_each(|(_, l)| l.total());
+        self.custom.par_iter_mut().for_each(|(_, l)| l.total());
+    }
+
+    /// Registers a language which isn't part of tokei's list of languages.
+    /// Files with one of its extensions are counted as that language instead,
+    /// and stored under its name in `custom`. Custom languages aren't
+    /// filtered by the `types` passed to the statistics methods.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.register_custom(CustomLanguage {
+    ///     name: "Acme".to_owned(),
+    ///     extensions: vec!["acme".to_owned()],
+    ///     line_comments: vec!["--".to_owned()],
+    ///     ..CustomLanguage::default()
+    /// });
+    ///
+    /// languages.get_statistics(&["."], vec![".git"], None);
+    /// println!("Acme code: {}", languages.custom()["Acme"].code);
+    /// ```
+    pub fn register_custom(&mut self, language: CustomLanguage) {
+        self.custom.entry(language.name.clone()).or_default();
+        self.custom_languages.push(language);
+    }
+
+    /// Sets how files are parsed by the statistics methods, see
+    /// `ParseConfig`. Custom languages still take precedence over its
+    /// `overrides`, and aren't affected by its `excluded_types`. The `Stats`
+    /// of files larger than its `max_file_bytes` are kept with `too_large`
+    /// set, and their languages are marked as inaccurate.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut config = ParseConfig {
+    ///     max_file_bytes: Some(10 * 1024 * 1024),
+    ///     excluded_types: vec![LanguageType::Markdown, LanguageType::Json],
+    ///     ..ParseConfig::default()
+    /// };
+    /// config.overrides.extensions.insert("h".to_owned(), LanguageType::CppHeader);
+    ///
+    /// let mut languages = Languages::new();
+    /// languages.set_parse_config(config);
+    /// languages.get_statistics(&["."], vec![".git", "target"], None);
+    ///
+    /// let too_large = languages.values()
+    ///     .flat_map(|language| &language.stats)
+    ///     .filter(|stats| stats.too_large)
+    ///     .count();
+    /// ```
+    pub fn set_parse_config(&mut self, config: ParseConfig) {
+        self.config = config;
+    }
+
+    /// Sets the reporter called as each file is done by the statistics
+    /// methods, replacing any previous one. See `Progress`.
+    pub fn set_progress<P: Progress + 'static>(&mut self, progress: P) {
+        self.progress = Reporter::new(progress);
+    }
+
+    /// Sets the cache of previously counted files used and updated by the
+    /// statistics methods. See `StatsCache`.
+    pub fn set_cache(&mut self, cache: StatsCache) {
+        self.cache = Some(cache);
+    }
+
+    /// Removes the cache, as updated by the statistics methods, so that it
+    /// can be stored for the next run.
+    pub fn take_cache(&mut self) -> Option<StatsCache> {
+        self.cache.take()
+    }
+
+    /// Sets whether files with identical contents, like copies of a vendored
+    /// dependency, are only counted once by the statistics methods. The
+    /// first of the files in order of their paths is kept, and files are
+    /// only identical to files of the same language.
+    ///
+    /// The contents are hashed as they're read for parsing, but files whose
+    /// statistics are cached are still read to hash them.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.set_dedup(true);
+    /// languages.get_statistics(&["."], vec![".git", "target"], None);
+    /// ```
+    pub fn set_dedup(&mut self, dedup: bool) {
+        self.dedup = dedup;
+    }
+
+    /// The statistics of the registered custom languages, keyed by their
+    /// names.
+    pub fn custom(&self) -> &BTreeMap<String, Language> {
+        &self.custom
     }
 
     /// Constructs a new, blank `Languages`.
@@ -114,6 +376,25 @@ impl Languages {
         Languages::default()
     }
 
+    /// Merges the statistics of `other` into these, adding to the languages
+    /// that are present in both and inserting the ones only `other` has.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["src"], vec![], None);
+    ///
+    /// let mut tests = Languages::new();
+    /// tests.get_statistics(&["tests"], vec![], None);
+    ///
+    /// languages.merge(tests);
+    /// ```
+    pub fn merge(&mut self, other: Languages) {
+        for (name, language) in other {
+            *self.inner.entry(name).or_default() += language;
+        }
+    }
+
     /// Creates a new map that only contains non empty languages.
     ///
     /// ```
@@ -137,6 +418,230 @@ impl Languages {
         }
         map
     }
+
+    /// Compares these statistics against a `previous` run, returning the
+    /// change for every language. Languages which are empty or missing on
+    /// one side are reported as added or removed.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut previous = Languages::new();
+    /// previous.get_statistics(&["base"], vec![".git"], None);
+    ///
+    /// let mut current = Languages::new();
+    /// current.get_statistics(&["head"], vec![".git"], None);
+    ///
+    /// let diff = current.diff(&previous);
+    /// for (language, delta) in &diff.languages {
+    ///     println!("{}: {:+} code", language, delta.code);
+    /// }
+    /// ```
+    pub fn diff(&self, previous: &Languages) -> LanguageDiff {
+        LanguageDiff::new(self, previous)
+    }
+
+    /// The totals of every language, including custom ones, with the
+    /// percentage of each kind of line.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["."], vec![".git", "target"], None);
+    ///
+    /// let total = languages.summary();
+    /// println!("{} files, {:.1}% code", total.files, total.code_percentage);
+    /// ```
+    pub fn summary(&self) -> Summary {
+        Summary::new(self.inner.values().chain(self.custom.values()))
+    }
+
+    /// Number of files of every language, including custom ones, decoded
+    /// from each encoding, see `Stats::encoding`. Files whose encoding isn't
+    /// known aren't included.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["."], vec![".git", "target"], None);
+    ///
+    /// for (encoding, files) in languages.encodings() {
+    ///     println!("{}: {} files", encoding, files);
+    /// }
+    /// ```
+    pub fn encodings(&self) -> BTreeMap<&'static str, usize> {
+        let mut encodings = BTreeMap::new();
+        let stats = self.inner.values().chain(self.custom.values()).flat_map(|l| &l.stats);
+
+        for encoding in stats.filter_map(|stats| stats.encoding) {
+            *encodings.entry(encoding).or_insert(0) += 1;
+        }
+
+        encodings
+    }
+
+    /// Sums up the totals of source code and prose languages separately, see
+    /// `LanguageType::is_literate`. Custom languages are counted as source
+    /// code.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.insert(LanguageType::Rust, Language { code: 10, lines: 10, ..Language::new() });
+    /// languages.insert(LanguageType::Markdown, Language { comments: 5, lines: 5, ..Language::new() });
+    ///
+    /// let (source, prose) = languages.summary_by_literate();
+    /// assert_eq!(source.lines, 10);
+    /// assert_eq!(prose.lines, 5);
+    /// ```
+    pub fn summary_by_literate(&self) -> (Summary, Summary) {
+        let (prose, source): (Vec<_>, Vec<_>) = self.inner.iter()
+            .partition(|(language_type, _)| language_type.is_literate());
+
+        let source = source.into_iter().map(|(_, language)| language).chain(self.custom.values());
+        let prose = prose.into_iter().map(|(_, language)| language);
+
+        (Summary::new(source), Summary::new(prose))
+    }
+
+    /// Estimates the effort, schedule and cost of developing the code of
+    /// every language, including custom ones, with the basic COCOMO model.
+    ///
+    /// ```no_run
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.get_statistics(&["."], vec![".git", "target"], None);
+    ///
+    /// let estimate = languages.cocomo(CocomoParams::default());
+    /// println!("{:.1} person-months", estimate.effort);
+    /// ```
+    pub fn cocomo(&self, params: CocomoParams) -> CocomoEstimate {
+        CocomoEstimate::new(self.summary().code, params)
+    }
+
+    /// Returns the languages sorted by the given category, without modifying
+    /// the underlying map. Languages which compare equal keep their original
+    /// order.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut languages = Languages::new();
+    /// languages.insert(LanguageType::Rust, Language { code: 10, ..Language::new() });
+    /// languages.insert(LanguageType::C, Language { code: 20, ..Language::new() });
+    ///
+    /// let sorted = languages.sort_by(Sort::Code, true);
+    /// assert_eq!(sorted[0].0, LanguageType::C);
+    ///
+    /// let sorted = languages.sort_by(Sort::Name, true);
+    /// assert_eq!(sorted[0].0, LanguageType::Rust);
+    /// ```
+    pub fn sort_by(&self, category: Sort, descending: bool) -> Vec<(LanguageType, &Language)> {
+        let mut languages = self.inner.iter()
+            .map(|(name, language)| (*name, language))
+            .collect::<Vec<_>>();
+
+        languages.sort_by(|a, b| {
+            let (a, b) = if descending { (b, a) } else { (a, b) };
+
+            match category {
+                Sort::Blanks => a.1.blanks.cmp(&b.1.blanks),
+                Sort::Comments => a.1.comments.cmp(&b.1.comments),
+                Sort::Code => a.1.code.cmp(&b.1.code),
+                Sort::Files => a.1.file_count().cmp(&b.1.file_count()),
+                Sort::Lines => a.1.lines.cmp(&b.1.lines),
+                Sort::Name => a.0.name().cmp(b.0.name()),
+            }
+        });
+
+        languages
+    }
+
+    /// Iterates over the statistics of every counted file, together with the
+    /// language it was counted as, without cloning them. Files of custom
+    /// languages aren't included.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// rust.add_stat(Stats { code: 10, ..Stats::new("main.rs".to_owned()) });
+    /// let mut c = Language::new();
+    /// c.add_stat(Stats { code: 20, ..Stats::new("main.c".to_owned()) });
+    ///
+    /// let mut languages = Languages::new();
+    /// languages.insert(LanguageType::Rust, rust);
+    /// languages.insert(LanguageType::C, c);
+    ///
+    /// // The largest files across all languages.
+    /// let mut files = languages.files().collect::<Vec<_>>();
+    /// files.sort_by(|a, b| b.1.code.cmp(&a.1.code));
+    /// assert_eq!(files[0].0, LanguageType::C);
+    /// assert_eq!(files[1].1.name, "main.rs");
+    /// ```
+    pub fn files(&self) -> impl Iterator<Item = (LanguageType, &Stats)> {
+        self.inner.iter()
+            .flat_map(|(&language_type, language)| {
+                language.stats.iter().map(move |stats| (language_type, stats))
+            })
+    }
+
+    /// Sums up the statistics of the files of every language, including
+    /// custom ones, by the directory they're in. Directories are cut off after
+    /// `depth` levels, so with a depth of `2` the files in `src/foo/` and
+    /// `src/foo/bar/` are both counted under `src/foo`. Files outside of any
+    /// directory are counted under `.`.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// rust.add_stat(Stats { code: 10, ..Stats::new("src/foo/a.rs".to_owned()) });
+    /// rust.add_stat(Stats { code: 20, ..Stats::new("src/foo/bar/b.rs".to_owned()) });
+    /// rust.add_stat(Stats { code: 5, ..Stats::new("build.rs".to_owned()) });
+    ///
+    /// let mut languages = Languages::new();
+    /// languages.insert(LanguageType::Rust, rust);
+    ///
+    /// let directories = languages.by_directory(2);
+    /// assert_eq!(directories["src/foo"].code, 30);
+    /// assert_eq!(directories["."].code, 5);
+    /// ```
+    pub fn by_directory(&self, depth: usize) -> BTreeMap<String, Stats> {
+        let mut directories = BTreeMap::new();
+        let stats = self.inner.values()
+            .chain(self.custom.values())
+            .flat_map(|language| &language.stats);
+
+        for stats in stats {
+            let directory = directory_prefix(&stats.name, depth);
+            *directories.entry(directory.clone()).or_insert_with(|| Stats::new(directory)) +=
+                stats.clone();
+        }
+
+        directories
+    }
+}
+
+/// The directory of the file `name`, cut off after `depth` directories, for
+/// `Languages::by_directory`.
+fn directory_prefix(name: &str, depth: usize) -> String {
+    let parent = Path::new(name).parent().unwrap_or_else(|| Path::new(""));
+    let mut prefix = PathBuf::new();
+    let mut directories = 0;
+
+    for component in parent.components() {
+        match component {
+            Component::CurDir => continue,
+            Component::Normal(_) if directories == depth => break,
+            Component::Normal(_) => directories += 1,
+            _ => {}
+        }
+
+        prefix.push(component);
+    }
+
+    if prefix.as_os_str().is_empty() {
+        ".".to_owned()
+    } else {
+        prefix.to_string_lossy().into_owned()
+    }
 }
 
 impl IntoIterator for Languages {
@@ -167,14 +672,12 @@ impl<'a> IntoIterator for &'a mut Languages {
     }
 }
 
+/// Adds the statistics of each language in the map to the same language in
+/// `self`, inserting the languages which aren't already present.
 impl AddAssign<BTreeMap<LanguageType, Language>> for Languages {
     fn add_assign(&mut self, rhs: BTreeMap<LanguageType, Language>) {
-
         for (name, language) in rhs {
-
-            if let Some(result) = self.inner.get_mut(&name) {
-                *result += language;
-            }
+            *self.inner.entry(name).or_default() += language;
         }
     }
 }
@@ -192,3 +695,172 @@ impl DerefMut for Languages {
         &mut self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(code: usize) -> Language {
+        let mut language = Language::new();
+        language.code = code;
+        language.lines = code;
+        language
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = Languages::new();
+        a.insert(LanguageType::Rust, language(10));
+
+        let mut b = Languages::new();
+        b.insert(LanguageType::Rust, language(10));
+        b.insert(LanguageType::C, language(5));
+
+        a.merge(b);
+        assert_eq!(a[&LanguageType::Rust].code, 20);
+        assert_eq!(a[&LanguageType::C].code, 5);
+    }
+
+    #[test]
+    fn add_assign_inserts_missing_languages() {
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, language(10));
+
+        let mut map = BTreeMap::new();
+        map.insert(LanguageType::Rust, language(10));
+        map.insert(LanguageType::C, language(5));
+
+        languages += map;
+        assert_eq!(languages[&LanguageType::Rust].code, 20);
+        assert_eq!(languages[&LanguageType::C].code, 5);
+    }
+
+    #[test]
+    fn files() {
+        let mut rust = Language::new();
+        rust.add_stat(Stats::new("a.rs".to_owned()));
+        rust.add_stat(Stats::new("b.rs".to_owned()));
+        let mut c = Language::new();
+        c.add_stat(Stats::new("main.c".to_owned()));
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, c);
+        languages.insert(LanguageType::Python, Language::new());
+
+        let files = languages.files()
+            .map(|(language_type, stats)| (language_type, &*stats.name))
+            .collect::<Vec<_>>();
+        assert_eq!(files, [
+            (LanguageType::C, "main.c"),
+            (LanguageType::Rust, "a.rs"),
+            (LanguageType::Rust, "b.rs"),
+        ]);
+
+        // The statistics are borrowed from the languages.
+        let first = languages.files().next().unwrap().1;
+        assert!(std::ptr::eq(first, &languages[&LanguageType::C].stats[0]));
+    }
+
+    #[test]
+    fn empty_file() {
+        let mut rust = Language::new();
+        rust.add_stat(Stats::new("empty.rs".to_owned()));
+        rust.total();
+
+        assert!(rust.is_empty());
+        assert!(rust.has_files());
+        assert_eq!(rust.file_count(), 1);
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, language(1));
+
+        // Languages whose files are all empty are removed, but still listed.
+        let kept = languages.remove_empty().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(kept, [&LanguageType::C]);
+        assert!(languages[&LanguageType::Rust].has_files());
+    }
+
+    #[test]
+    fn encodings() {
+        let parse = |name: &str, text: &[u8]| {
+            LanguageType::Rust.parse_from_bytes(name.to_owned().into(), text).unwrap()
+        };
+
+        let mut rust = Language::new();
+        rust.add_stat(parse("a.rs", b"fn a() {}\n"));
+        rust.add_stat(parse("b.rs", b"\xef\xbb\xbffn b() {}\n"));
+        rust.add_stat(parse("c.rs", b"// caf\xe9\n"));
+        // The encoding of text read from a reader isn't known.
+        let read = LanguageType::Rust.parse_from_reader("d.rs".into(), &b"fn d() {}\n"[..]);
+        rust.add_stat(read.unwrap());
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+
+        let encodings = languages.encodings().into_iter().collect::<Vec<_>>();
+        assert_eq!(encodings, [("ISO-8859-1", 1), ("UTF-8", 2)]);
+    }
+
+    #[test]
+    fn by_directory() {
+        let mut rust = Language::new();
+        rust.add_stat(Stats { code: 1, ..Stats::new("./src/a.rs".to_owned()) });
+        rust.add_stat(Stats { code: 2, ..Stats::new("./src/foo/b.rs".to_owned()) });
+        rust.add_stat(Stats { code: 4, ..Stats::new("./main.rs".to_owned()) });
+        rust.add_stat(Stats { code: 8, ..Stats::new("/abs/lib/c.rs".to_owned()) });
+        let mut c = Language::new();
+        c.add_stat(Stats { code: 16, ..Stats::new("src/foo/d.c".to_owned()) });
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, c);
+
+        let code = |depth| {
+            languages.by_directory(depth).into_iter()
+                .map(|(directory, stats)| (directory, stats.code))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(code(1), [
+            (".".to_owned(), 4),
+            ("/abs".to_owned(), 8),
+            ("src".to_owned(), 19),
+        ]);
+        assert_eq!(code(2), [
+            (".".to_owned(), 4),
+            ("/abs/lib".to_owned(), 8),
+            ("src".to_owned(), 1),
+            ("src/foo".to_owned(), 18),
+        ]);
+        assert_eq!(code(0), [(".".to_owned(), 23), ("/".to_owned(), 8)]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserialize_recomputes_totals() {
+        use serde_json;
+
+        let mut rust = Language::new();
+        rust.add_stat(Stats { code: 3, lines: 3, ..Stats::new("a.rs".to_owned()) });
+        rust.add_stat(Stats { code: 2, blanks: 1, lines: 3, ..Stats::new("b.rs".to_owned()) });
+        rust.total();
+        // Totals which drifted from the files.
+        rust.code = 100;
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, language(10));
+
+        let json = serde_json::to_string(&languages).unwrap();
+        let languages: Languages = serde_json::from_str(&json).unwrap();
+
+        let rust = &languages[&LanguageType::Rust];
+        assert_eq!(rust.code, rust.stats.iter().map(|stats| stats.code).sum::<usize>());
+        assert_eq!((rust.code, rust.blanks, rust.lines, rust.files), (5, 1, 6, 2));
+
+        // Languages without the statistics of their files keep their totals.
+        assert_eq!(languages[&LanguageType::C].code, 10);
+    }
+}
@@ -4,21 +4,31 @@
 
 pub mod languages;
 pub mod language_type;
-mod syntax;
+mod custom;
+pub mod syntax;
+#[cfg(feature = "jupyter")]
+mod jupyter;
 
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::mem;
-use std::ops::AddAssign;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
 
+pub use self::custom::{parse_custom, CustomLanguage};
+pub(crate) use self::custom::parse_custom_with_config;
 pub use self::languages::Languages;
+pub use self::syntax::SyntaxCounter;
 pub use self::language_type::*;
 
 use sort::Sort::*;
 use sort::Sort;
-use stats::Stats;
+use stats::{self, LineEnding, Stats};
+use summary::Summary;
 
 /// Struct representing a single Language.
 #[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Language {
     /// Number of blank lines.
     pub blanks: usize,
@@ -26,12 +36,60 @@ pub struct Language {
     pub code: usize,
     /// Number of comments(both single, and multi-line)
     pub comments: usize,
+    /// Number of documentation comments. (_included in `comments`_)
+    #[cfg_attr(feature = "io", serde(default))]
+    pub doc_comments: usize,
+    /// Number of lines starting a function or other definition.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub functions: usize,
+    /// Number of markers like `TODO` or `FIXME` within comments.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub todos: usize,
     /// Number of total lines.
     pub lines: usize,
+    /// Number of bytes across all files.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub bytes: usize,
+    /// Length in bytes of the longest line across all files.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub max_line_length: usize,
+    /// Number of files, as of the last call to `total`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub files: usize,
+    /// Number of files with at least one line of code, as of the last call to
+    /// `total`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub files_with_code: usize,
+    /// Number of files with comments but no code, like files with only a
+    /// license header, as of the last call to `total`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub files_comment_only: usize,
+    /// Number of files with neither code nor comments, including empty
+    /// files, as of the last call to `total`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub files_blank_only: usize,
+    /// Number of generated files, whose lines weren't counted, as of the last
+    /// call to `total`. These aren't included in the kinds of files above.
+    /// See `ParseConfig::skip_generated`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub files_generated: usize,
+    /// Number of files which were too large to be read, as of the last call
+    /// to `total`. These aren't included in the kinds of files above. See
+    /// `ParseConfig::max_file_bytes`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub files_too_large: usize,
+    /// Number of files with each kind of line endings, as of the last call to
+    /// `total`. Files without any line endings aren't included.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub line_endings: BTreeMap<LineEnding, usize>,
     /// A collection of statistics based on the files provide from `files`
     pub stats: Vec<Stats>,
     /// Whether this language had problems with file parsing
     pub inaccurate: bool,
+    /// Number of files of this language which couldn't be parsed, and so
+    /// aren't included in the counts.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub skipped: usize,
 }
 
 impl Language {
@@ -50,32 +108,144 @@ impl Language {
         self.stats.push(stat);
     }
 
+    /// The number of files which were counted as this language. Files which
+    /// were skipped aren't included.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// rust.add_stat(Stats::new("main.rs".to_owned()));
+    /// rust.add_stat(Stats::new("lib.rs".to_owned()));
+    /// rust.add_skipped();
+    ///
+    /// assert_eq!(rust.file_count(), 2);
+    /// ```
+    pub fn file_count(&self) -> usize {
+        self.stats.len()
+    }
+
+    /// Whether any files were counted as this language, even if they're all
+    /// empty. Files which were skipped aren't included.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// assert!(!rust.has_files());
+    ///
+    /// rust.add_stat(Stats::new("empty.rs".to_owned()));
+    /// rust.total();
+    ///
+    /// assert!(rust.has_files() && rust.is_empty());
+    /// ```
+    pub fn has_files(&self) -> bool {
+        !self.stats.is_empty()
+    }
+
     /// Marks this language as possibly not reflecting correct stats.
     #[inline]
     pub fn mark_inaccurate(&mut self) {
         self.inaccurate = true;
     }
 
+    /// Records a file of this language which couldn't be parsed, marking the
+    /// language as inaccurate.
+    pub fn add_skipped(&mut self) {
+        self.skipped += 1;
+        self.mark_inaccurate();
+    }
+
     /// Totals up all the statistics currently in the language.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// rust.add_stat(Stats { code: 2, comments: 1, ..Stats::new("main.rs".to_owned()) });
+    /// rust.add_stat(Stats { comments: 3, blanks: 1, ..Stats::new("lib.rs".to_owned()) });
+    /// rust.add_stat(Stats::new("empty.rs".to_owned()));
+    /// rust.total();
+    ///
+    /// assert_eq!(rust.files, 3);
+    /// assert_eq!(rust.files_with_code, 1);
+    /// assert_eq!(rust.files_comment_only, 1);
+    /// assert_eq!(rust.files_blank_only, 1);
+    /// ```
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// rust.add_stat(LanguageType::Rust.parse_from_str("a.rs".into(), "fn a() {}\n"));
+    /// rust.add_stat(LanguageType::Rust.parse_from_str("b.rs".into(), "fn b() {}\r\n"));
+    /// rust.add_stat(LanguageType::Rust.parse_from_str("c.rs".into(), "fn c() {}\n"));
+    /// rust.total();
+    ///
+    /// assert_eq!(rust.line_endings[&LineEnding::Lf], 2);
+    /// assert_eq!(rust.line_endings[&LineEnding::Crlf], 1);
+    /// ```
     pub fn total(&mut self) {
         let mut blanks = 0;
         let mut code = 0;
         let mut comments = 0;
+        let mut doc_comments = 0;
+        let mut functions = 0;
+        let mut todos = 0;
+        let mut bytes = 0;
+        let mut max_line_length = 0;
+        let mut files_with_code = 0;
+        let mut files_comment_only = 0;
+        let mut files_blank_only = 0;
+        let mut files_generated = 0;
+        let mut files_too_large = 0;
+        let mut line_endings = BTreeMap::new();
 
         for stat in &self.stats {
             blanks += stat.blanks;
             code += stat.code;
             comments += stat.comments;
+            doc_comments += stat.doc_comments;
+            functions += stat.functions;
+            todos += stat.todos;
+            bytes += stat.bytes;
+            max_line_length = max_line_length.max(stat.max_line_length);
+
+            if stat.too_large {
+                files_too_large += 1;
+            } else if stat.generated {
+                files_generated += 1;
+            } else if stat.code > 0 {
+                files_with_code += 1;
+            } else if stat.comments > 0 {
+                files_comment_only += 1;
+            } else {
+                files_blank_only += 1;
+            }
+
+            if let Some(line_ending) = stat.line_ending {
+                *line_endings.entry(line_ending).or_insert(0) += 1;
+            }
         }
 
         self.blanks = blanks;
         self.code = code;
         self.comments = comments;
+        self.doc_comments = doc_comments;
+        self.functions = functions;
+        self.todos = todos;
+        self.bytes = bytes;
+        self.max_line_length = max_line_length;
+        self.files = self.stats.len();
+        self.files_with_code = files_with_code;
+        self.files_comment_only = files_comment_only;
+        self.files_blank_only = files_blank_only;
+        self.files_generated = files_generated;
+        self.files_too_large = files_too_large;
+        self.line_endings = line_endings;
         self.lines = blanks + code + comments;
     }
 
-    /// Checks if the language is empty. Empty meaning it doesn't have any
-    /// statistics.
+    /// Checks if the language is empty. Empty meaning none of its lines were
+    /// counted, and no files of it were skipped. A language whose files are
+    /// all empty is empty too, see `has_files` to tell it apart from one
+    /// without files.
     ///
     /// ```
     /// # use tokei::*;
@@ -87,32 +257,136 @@ impl Language {
         self.code == 0 &&
         self.comments == 0 &&
         self.blanks == 0 &&
-        self.lines == 0
+        self.lines == 0 &&
+        self.skipped == 0
+    }
+
+    /// The fraction of lines which are code, or `0.0` if there are no lines.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let rust = Language { code: 1, blanks: 1, lines: 2, ..Language::new() };
+    /// assert_eq!(rust.code_ratio(), 0.5);
+    /// assert_eq!(Language::new().code_ratio(), 0.0);
+    /// ```
+    pub fn code_ratio(&self) -> f64 {
+        stats::ratio(self.code, self.lines)
+    }
+
+    /// The fraction of lines which are comments, or `0.0` if there are no
+    /// lines.
+    pub fn comment_ratio(&self) -> f64 {
+        stats::ratio(self.comments, self.lines)
+    }
+
+    /// The fraction of lines which are blank, or `0.0` if there are no lines.
+    pub fn blank_ratio(&self) -> f64 {
+        stats::ratio(self.blanks, self.lines)
+    }
+
+    /// The totals of this language, with the percentage of each kind of
+    /// line.
+    pub fn summary(&self) -> Summary {
+        Summary::new(Some(self))
     }
 
     /// Sorts each of the `Stats` structs contained in the language based
     /// on what category is provided
     /// panic!'s if given the wrong category.
     pub fn sort_by(&mut self, category: Sort) {
-        match category {
-            Blanks => self.stats.sort_by(|a, b| b.blanks.cmp(&a.blanks)),
-            Comments => self.stats.sort_by(|a, b| b.comments.cmp(&a.comments)),
-            Code => self.stats.sort_by(|a, b| b.code.cmp(&a.code)),
-            Files => self.stats.sort_by(|a, b| a.name.cmp(&b.name)),
-            Lines => self.stats.sort_by(|a, b| b.lines.cmp(&a.lines)),
-        }
+        self.stats.sort_by(|a, b| compare_stats(category, a, b));
     }
 
+    /// Returns the `Stats` of each file ordered by the provided category,
+    /// without modifying the language. Counts are sorted largest first, and
+    /// `Files` and `Name` sort by file name. Files with equal counts are
+    /// ordered by name.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let mut rust = Language::new();
+    /// rust.add_stat(Stats { code: 5, ..Stats::new("b.rs".to_owned()) });
+    /// rust.add_stat(Stats { code: 10, ..Stats::new("c.rs".to_owned()) });
+    /// rust.add_stat(Stats { code: 5, ..Stats::new("a.rs".to_owned()) });
+    ///
+    /// let names = rust.sorted_stats(Sort::Code)
+    ///     .into_iter()
+    ///     .map(|stats| &*stats.name)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(names, ["c.rs", "a.rs", "b.rs"]);
+    /// ```
+    pub fn sorted_stats(&self, category: Sort) -> Vec<&Stats> {
+        let mut stats = self.stats.iter().collect::<Vec<_>>();
+        stats.sort_by(|a, b| compare_stats(category, a, b));
+        stats
+    }
+}
+
+fn compare_stats(category: Sort, a: &Stats, b: &Stats) -> Ordering {
+    let ordering = match category {
+        Blanks => b.blanks.cmp(&a.blanks),
+        Comments => b.comments.cmp(&a.comments),
+        Code => b.code.cmp(&a.code),
+        Files | Name => Ordering::Equal,
+        Lines => b.lines.cmp(&a.lines),
+    };
+
+    ordering.then_with(|| a.name.cmp(&b.name))
 }
 
 impl AddAssign for Language {
     fn add_assign(&mut self, mut rhs: Self) {
         self.lines += rhs.lines;
+        self.bytes += rhs.bytes;
+        self.max_line_length = self.max_line_length.max(rhs.max_line_length);
+        self.files += rhs.files;
+        self.files_with_code += rhs.files_with_code;
+        self.files_comment_only += rhs.files_comment_only;
+        self.files_blank_only += rhs.files_blank_only;
+        self.files_generated += rhs.files_generated;
+        self.files_too_large += rhs.files_too_large;
+
+        for (line_ending, files) in mem::take(&mut rhs.line_endings) {
+            *self.line_endings.entry(line_ending).or_insert(0) += files;
+        }
+
         self.comments += rhs.comments;
+        self.doc_comments += rhs.doc_comments;
+        self.functions += rhs.functions;
+        self.todos += rhs.todos;
         self.blanks += rhs.blanks;
         self.code += rhs.code;
         self.stats.extend(mem::replace(&mut rhs.stats, Vec::new()));
+        self.skipped += rhs.skipped;
         self.inaccurate |= rhs.inaccurate
     }
 }
 
+/// Combines two languages, summing their counts and keeping the `Stats` of
+/// both.
+///
+/// ```
+/// # use tokei::*;
+/// let a = Language { code: 10, lines: 10, ..Language::new() };
+/// let b = Language { code: 5, blanks: 1, lines: 6, ..Language::new() };
+///
+/// let total: Language = vec![a.clone(), b.clone()].into_iter().sum();
+/// assert_eq!((a + b).code, total.code);
+/// assert_eq!(total.lines, 16);
+/// ```
+impl Add for Language {
+    type Output = Language;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl Sum for Language {
+    fn sum<I: Iterator<Item = Language>>(iter: I) -> Self {
+        iter.fold(Language::new(), Add::add)
+    }
+}
+
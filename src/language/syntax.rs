@@ -1,38 +1,331 @@
+//! The parser which tracks the quotes and comments of a language.
+
 use log::Level::Trace;
 
 use super::language_type::LanguageType;
+use stats::LineKind;
+use utils::bytes::Bytes;
 
-pub(crate) struct SyntaxCounter {
+/// The state machine tracking the quotes and comments of a language, which
+/// tokei uses to classify each line.
+///
+/// A line is parsed by stepping through it one window at a time, where a
+/// window is the rest of the line starting at the current byte. When one of
+/// the `parse_*` methods returns a length, that many bytes were consumed and
+/// the state has been updated. The state carries over to the following lines.
+///
+/// ```
+/// # use tokei::LanguageType;
+/// use tokei::syntax::SyntaxCounter;
+///
+/// let mut syntax = SyntaxCounter::new(LanguageType::Rust);
+/// let line = br#"let s = "/* not a comment */"; // comment"#;
+///
+/// let mut i = 0;
+/// let mut comment_at = None;
+///
+/// while i < line.len() {
+///     let window = &line[i..];
+///
+///     let skip = syntax.parse_end_of_quote(window)
+///         .or_else(|| syntax.parse_end_of_multi_line(window))
+///         .or_else(|| syntax.parse_quote(window))
+///         .or_else(|| syntax.parse_multi_line_comment(window));
+///
+///     if let Some(skip) = skip {
+///         i += skip;
+///         continue;
+///     }
+///
+///     if syntax.parse_line_comment(window) {
+///         comment_at = Some(i);
+///         break;
+///     }
+///
+///     i += 1;
+/// }
+///
+/// assert_eq!(comment_at, Some(31));
+/// assert!(!syntax.in_quote() && !syntax.in_comment());
+/// ```
+pub struct SyntaxCounter<'a> {
     pub(crate) is_fortran: bool,
+    pub(crate) is_fixed_form: bool,
     pub(crate) allows_nested: bool,
-    pub(crate) line_comments: &'static [&'static str],
-    pub(crate) multi_line_comments: &'static [(&'static str, &'static str)],
-    pub(crate) nested_comments: &'static [(&'static str, &'static str)],
-    pub(crate) quotes: &'static [(&'static str, &'static str)],
-    pub(crate) stack: Vec<&'static str>,
-    pub(crate) quote: Option<&'static str>,
+    pub(crate) char_literals: bool,
+    pub(crate) line_continuation: bool,
+    pub(crate) continued_comments: bool,
+    pub(crate) prefixed_interpolations: bool,
+    pub(crate) line_comments: &'a [&'a str],
+    pub(crate) not_line_comments: &'a [&'a str],
+    pub(crate) multi_line_comments: &'a [(&'a str, &'a str)],
+    pub(crate) doc_line_comments: &'a [&'a str],
+    pub(crate) doc_multi_line_comments: &'a [(&'a str, &'a str)],
+    pub(crate) nested_comments: &'a [(&'a str, &'a str)],
+    pub(crate) function_patterns: &'a [&'a str],
+    pub(crate) quotes: &'a [(&'a str, &'a str)],
+    pub(crate) verbatim_quotes: &'a [(&'a str, &'a str)],
+    pub(crate) literal_quotes: &'a [(&'a str, &'a str)],
+    pub(crate) raw_quotes: &'a [(&'a str, &'a str)],
+    pub(crate) interpolations: &'a [(&'a str, &'a str)],
+    pub(crate) line_quotes: &'a [&'a str],
+    pub(crate) heredoc_delimiters: &'a [&'a str],
+    pub(crate) todo_markers: &'a [String],
+    /// Number of columns between tab stops when measuring lines.
+    pub(crate) tab_width: usize,
+    pub(crate) stack: Vec<&'a str>,
+    pub(crate) quote: Option<&'a str>,
+    pub(crate) quote_is_verbatim: bool,
+    /// Whether the interpolations are parsed inside of the current quote.
+    pub(crate) quote_interpolates: bool,
+    /// The number of `#` closing the current quote, if it's a raw quote.
+    pub(crate) raw_hashes: Option<usize>,
+    /// The interpolated expressions currently open, innermost last.
+    pub(crate) interpolation: Vec<Interpolation<'a>>,
+    /// The delimiter terminating the currently open heredoc, and whether it
+    /// may be indented.
+    pub(crate) heredoc: Option<(Vec<u8>, bool)>,
+    pub(crate) in_doc_comment: bool,
+    /// The kind of the line continued by a trailing backslash on the
+    /// previous line, and whether it was continued within a line comment.
+    pub(crate) continued: Option<(LineKind, bool)>,
+    /// Whether a byte starts any of the tokens, indexed by the byte. Windows
+    /// starting with another byte can't change the state.
+    pub(crate) token_starts: [bool; 256],
+}
+
+/// An interpolated expression inside of a string, like `${name}`.
+pub(crate) struct Interpolation<'a> {
+    /// The quote to return to at the end of the expression.
+    quote: &'a str,
+    quote_is_verbatim: bool,
+    start: &'a str,
+    end: &'a str,
+    /// Number of blocks opened inside of the expression, like the braces of
+    /// a lambda in `${list.map { it }}`.
+    depth: usize,
 }
 
-impl SyntaxCounter {
-    pub(crate) fn new(language: LanguageType) -> Self {
-        Self {
+impl SyntaxCounter<'static> {
+    /// Create a counter with the quotes and comments of `language`, outside
+    /// of any quote or comment.
+    pub fn new(language: LanguageType) -> Self {
+        let mut counter = Self {
             is_fortran: language.is_fortran(),
+            is_fixed_form: language == LanguageType::FortranLegacy,
             allows_nested: language.allows_nested(),
+            char_literals: language.char_literals(),
+            line_continuation: language.line_continuation(),
+            continued_comments: language.continued_comments(),
+            prefixed_interpolations: language.prefixed_interpolations(),
             line_comments: language.line_comments(),
+            not_line_comments: language.not_line_comments(),
             multi_line_comments: language.multi_line_comments(),
+            doc_line_comments: language.doc_line_comments(),
+            doc_multi_line_comments: language.doc_multi_line_comments(),
             nested_comments: language.nested_comments(),
+            function_patterns: language.function_patterns(),
             quotes: language.quotes(),
+            verbatim_quotes: language.verbatim_quotes(),
+            literal_quotes: language.literal_quotes(),
+            raw_quotes: language.raw_quotes(),
+            interpolations: language.interpolations(),
+            line_quotes: language.line_quotes(),
+            heredoc_delimiters: language.heredoc_delimiters(),
+            todo_markers: &[],
+            tab_width: 1,
+            stack: Vec::with_capacity(1),
+            quote: None,
+            quote_is_verbatim: false,
+            quote_interpolates: true,
+            raw_hashes: None,
+            interpolation: Vec::new(),
+            heredoc: None,
+            in_doc_comment: false,
+            continued: None,
+            token_starts: [false; 256],
+        };
+
+        counter.token_starts = counter.find_token_starts();
+        counter
+    }
+}
+
+impl<'a> SyntaxCounter<'a> {
+    /// Create a counter for a language which isn't generated, with only
+    /// the given comments and quotes.
+    pub(crate) fn custom(
+        line_comments: &'a [&'a str],
+        multi_line_comments: &'a [(&'a str, &'a str)],
+        quotes: &'a [(&'a str, &'a str)],
+    ) -> Self {
+        let mut counter = Self {
+            is_fortran: false,
+            is_fixed_form: false,
+            allows_nested: false,
+            char_literals: false,
+            line_continuation: false,
+            continued_comments: false,
+            prefixed_interpolations: false,
+            line_comments,
+            not_line_comments: &[],
+            multi_line_comments,
+            doc_line_comments: &[],
+            doc_multi_line_comments: &[],
+            nested_comments: &[],
+            function_patterns: &[],
+            quotes,
+            verbatim_quotes: &[],
+            literal_quotes: &[],
+            raw_quotes: &[],
+            interpolations: &[],
+            line_quotes: &[],
+            heredoc_delimiters: &[],
+            todo_markers: &[],
+            tab_width: 1,
             stack: Vec::with_capacity(1),
             quote: None,
+            quote_is_verbatim: false,
+            quote_interpolates: true,
+            raw_hashes: None,
+            interpolation: Vec::new(),
+            heredoc: None,
+            in_doc_comment: false,
+            continued: None,
+            token_starts: [false; 256],
+        };
+
+        counter.token_starts = counter.find_token_starts();
+        counter
+    }
+
+    /// Finds the bytes which any of the tokens checked while stepping through
+    /// a line start with, including the `\\` escaping the end of a quote.
+    fn find_token_starts(&self) -> [bool; 256] {
+        let pairs = self.multi_line_comments.iter()
+            .chain(self.nested_comments)
+            .chain(self.quotes)
+            .chain(self.verbatim_quotes)
+            .chain(self.literal_quotes)
+            .chain(self.interpolations)
+            .flat_map(|&(start, end)| vec![start, end]);
+
+        // Raw quotes are found from their quote, looking back for the
+        // prefix, which is often a common letter.
+        let raw_quotes = self.raw_quotes.iter().map(|&(_, quote)| quote);
+
+        // Blocks inside of interpolations are opened by the last character
+        // of their start.
+        let interpolation_blocks = self.interpolations.iter()
+            .map(|&(start, _)| &start[start.len().saturating_sub(1)..]);
+
+        let tokens = self.line_comments.iter()
+            .chain(self.line_quotes)
+            .chain(self.heredoc_delimiters)
+            .cloned()
+            .chain(pairs)
+            .chain(raw_quotes)
+            .chain(interpolation_blocks)
+            .chain(Some("\\"))
+            .chain(if self.char_literals { Some("'") } else { None });
+
+        let mut token_starts = [false; 256];
+
+        for token in tokens {
+            match token.as_bytes().first() {
+                Some(&b) => token_starts[b as usize] = true,
+                // An empty token matches every window.
+                None => return [true; 256],
+            }
+        }
+
+        token_starts
+    }
+
+    /// Checks whether a line that has been classified as a comment is
+    /// documentation. `had_multi_line` is whether the line started inside a
+    /// multi line comment, in which case it is documentation if the comment
+    /// being continued was opened as one.
+    #[inline]
+    pub(crate) fn is_doc_comment(&self, line: &[u8], had_multi_line: bool) -> bool {
+        if had_multi_line {
+            return self.in_doc_comment;
         }
+
+        self.doc_line_comments.iter().any(|c| line.starts_with(c.as_bytes())) ||
+        self.doc_multi_line_comments.iter().any(|(s, _)| line.starts_with(s.as_bytes()))
+    }
+
+    /// Checks whether a line starts a function, which is only the case if
+    /// the line doesn't start inside of a quote or comment.
+    #[inline]
+    pub(crate) fn is_function(&self, line: &[u8]) -> bool {
+        !self.function_patterns.is_empty() &&
+        self.quote.is_none() &&
+        self.stack.is_empty() &&
+        self.function_patterns.iter().any(|p| line.starts_with(p.as_bytes()))
+    }
+
+    /// The continuation of a line classified as `kind` on the next line, if
+    /// it ends with an unescaped backslash outside of quotes and multi line
+    /// comments. `in_line_comment` is whether the line ends in a line
+    /// comment, which is only continued in some languages.
+    #[inline]
+    pub(crate) fn continuation(&self, line: &[u8], kind: LineKind, in_line_comment: bool)
+        -> Option<(LineKind, bool)>
+    {
+        let backslashes = line.iter().rev().take_while(|&&b| b == b'\\').count();
+        let continues = self.line_continuation &&
+            backslashes % 2 == 1 &&
+            self.quote.is_none() &&
+            self.stack.is_empty() &&
+            (!in_line_comment || self.continued_comments);
+
+        if continues { Some((kind, in_line_comment)) } else { None }
     }
 
+    /// Counts the occurrences of the todo markers in a line that has been
+    /// classified as a comment. Markers which are part of a longer word, like
+    /// `TODO` in `TODOS`, aren't counted.
     #[inline]
-    pub(crate) fn important_syntax(&self) -> impl Iterator<Item = &str> {
+    pub(crate) fn count_todos(&self, line: &[u8]) -> usize {
+        self.todo_markers.iter()
+            .filter(|marker| !marker.is_empty())
+            .map(|marker| count_words(line, marker.as_bytes()))
+            .sum()
+    }
+
+    /// Whether the counter is inside of a quote.
+    pub fn in_quote(&self) -> bool {
+        self.quote.is_some()
+    }
+
+    /// Whether the counter is inside of a multi line comment.
+    pub fn in_comment(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    /// The number of multi line comments the counter is inside of, which is
+    /// more than one inside of nested comments, like `/+ /+ +/ +/` in D.
+    pub fn comment_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// The tokens which start a quote, multi line comment or heredoc. A line
+    /// without any of these can't change the state of the counter.
+    #[inline]
+    pub fn important_syntax(&self) -> impl Iterator<Item = &str> {
+        // Raw quotes always contain their quote, unlike their prefix which
+        // is often common.
         self.quotes.into_iter()
+            .chain(self.verbatim_quotes)
+            .chain(self.literal_quotes)
             .map(|(s, _)| *s)
+            .chain(self.raw_quotes.iter().map(|(_, quote)| *quote))
             .chain(self.multi_line_comments.into_iter().map(|(s, _)| *s))
             .chain(self.nested_comments.into_iter().map(|(s, _)| *s))
+            .chain(self.line_quotes.iter().cloned())
+            .chain(self.heredoc_delimiters.iter().cloned())
     }
 
     #[inline]
@@ -42,12 +335,19 @@ impl SyntaxCounter {
             .chain(self.nested_comments.into_iter().map(|(s, _)| s))
     }
 
+    /// Checks whether the window starts a line comment, which runs until the
+    /// end of the line. Line comments can't start inside of a quote or
+    /// multi line comment.
     #[inline]
-    pub(crate) fn parse_line_comment(&self, window: &[u8]) -> bool {
+    pub fn parse_line_comment(&self, window: &[u8]) -> bool {
         if self.quote.is_some() || !self.stack.is_empty() {
             return false
         }
 
+        if self.is_not_line_comment(window) {
+            return false;
+        }
+
         for comment in self.line_comments {
             if window.starts_with(comment.as_bytes()) {
                 trace!("Start {:?}", comment);
@@ -58,16 +358,91 @@ impl SyntaxCounter {
         false
     }
 
+    /// Checks whether the window starts with a token which looks like a line
+    /// comment but isn't one, like PHP's `#[` attributes.
+    #[inline]
+    pub(crate) fn is_not_line_comment(&self, window: &[u8]) -> bool {
+        self.not_line_comments.iter().any(|token| window.starts_with(token.as_bytes()))
+    }
+
+    /// Checks whether the window starts a string which runs until the end of
+    /// the line, like Zig's `\\` multiline strings, in which case nothing
+    /// else on the line can start a quote or comment.
+    #[inline]
+    pub fn parse_line_quote(&self, window: &[u8]) -> bool {
+        if self.quote.is_some() || !self.stack.is_empty() {
+            return false
+        }
+
+        for quote in self.line_quotes {
+            if window.starts_with(quote.as_bytes()) {
+                trace!("Start {:?}", quote);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Checks whether the window starts a quote, returning the length of the
+    /// start. Quotes can't start inside of another quote or a multi line
+    /// comment.
     #[inline]
-    pub(crate) fn parse_quote(&mut self, window: &[u8]) -> Option<usize> {
-        if !self.stack.is_empty() {
+    pub fn parse_quote(&mut self, window: &[u8]) -> Option<usize> {
+        self.parse_quote_after(&[], window)
+    }
+
+    /// Checks whether the window starts a quote like `parse_quote`, where
+    /// `before` is the part of the line preceding the window.
+    #[inline]
+    pub(crate) fn parse_quote_after(&mut self, before: &[u8], window: &[u8]) -> Option<usize> {
+        // Other quotes inside of a quote, like `"` in a `"""` docstring, are
+        // just part of the string.
+        if self.quote.is_some() || !self.stack.is_empty() {
             return None
         }
 
+        if self.char_literals && window.starts_with(b"'") {
+            return Some(char_literal_len(window).unwrap_or(1));
+        }
+
+        if let Some(len) = self.parse_raw_quote(before, window) {
+            return Some(len);
+        }
+
+        // Only strings prefixed with an interpolator, like Scala's `s"..."`,
+        // interpolate in some languages.
+        let interpolates = !self.prefixed_interpolations ||
+            before.last().map_or(false, |&c| is_identifier(c));
+
+        for &(start, end) in self.verbatim_quotes {
+            if window.starts_with(start.as_bytes()) {
+                trace!("Start verbatim {:?}", start);
+                self.quote = Some(end);
+                self.quote_is_verbatim = true;
+                self.quote_interpolates = interpolates;
+                return Some(start.len());
+            }
+        }
+
+        // Literal quotes end at their first closing quote, like a raw quote
+        // without any `#`.
+        for &(start, end) in self.literal_quotes {
+            if window.starts_with(start.as_bytes()) {
+                trace!("Start literal {:?}", start);
+                self.quote = Some(end);
+                self.quote_is_verbatim = false;
+                self.raw_hashes = Some(0);
+                return Some(start.len());
+            }
+        }
+
         for &(start, end) in self.quotes {
             if window.starts_with(start.as_bytes()) {
                 trace!("Start {:?}", start);
                 self.quote = Some(end);
+                self.quote_is_verbatim = false;
+                self.quote_interpolates = interpolates;
                 return Some(start.len());
             }
         }
@@ -75,26 +450,148 @@ impl SyntaxCounter {
         None
     }
 
+    /// Checks whether the window starts a raw quote, either at its prefix or
+    /// at its quote, in which case the prefix and any `#` are looked for at
+    /// the end of `before`. The prefix can't be the end of an identifier, so
+    /// `bar"x"` isn't a raw string.
     #[inline]
-    pub(crate) fn parse_multi_line_comment(&mut self, window: &[u8])
+    fn parse_raw_quote(&mut self, before: &[u8], window: &[u8]) -> Option<usize> {
+        for &(prefix, quote) in self.raw_quotes {
+            let (prefix, quote_bytes) = (prefix.as_bytes(), quote.as_bytes());
+
+            let (hashes, len, before) = if window.starts_with(prefix) {
+                let rest = &window[prefix.len()..];
+                let hashes = rest.iter().take_while(|&&c| c == b'#').count();
+
+                if !rest[hashes..].starts_with(quote_bytes) {
+                    continue;
+                }
+
+                (hashes, prefix.len() + hashes + quote.len(), before)
+            } else if window.starts_with(quote_bytes) {
+                let hashes = before.iter().rev().take_while(|&&c| c == b'#').count();
+                let before = &before[..before.len() - hashes];
+
+                if !before.ends_with(prefix) {
+                    continue;
+                }
+
+                (hashes, quote.len(), &before[..before.len() - prefix.len()])
+            } else {
+                continue;
+            };
+
+            if before.last().map_or(false, |&c| is_identifier(c)) {
+                continue;
+            }
+
+            trace!("Start raw {:?} with {} #", quote, hashes);
+            self.quote = Some(quote);
+            self.quote_is_verbatim = false;
+            self.raw_hashes = Some(hashes);
+            return Some(len);
+        }
+
+        None
+    }
+
+    /// Checks if the window opens a heredoc, in which case the following
+    /// lines are part of it until `end_of_heredoc` matches. The delimiter
+    /// has to follow the operator directly, optionally quoted. `before` is
+    /// the part of the line preceding the window, so that a here-string
+    /// like `<<<word` isn't mistaken for one.
+    #[inline]
+    pub(crate) fn parse_heredoc(&mut self, before: &[u8], window: &[u8]) -> Option<usize> {
+        if self.quote.is_some() || !self.stack.is_empty() || self.heredoc.is_some() {
+            return None
+        }
+
+        if before.last() == Some(&b'<') {
+            return None
+        }
+
+        for start in self.heredoc_delimiters {
+            if !window.starts_with(start.as_bytes()) {
+                continue;
+            }
+
+            let rest = &window[start.len()..];
+            let quote = match rest.first() {
+                Some(&c) if c == b'"' || c == b'\'' || c == b'`' => Some(c),
+                _ => None,
+            };
+            let ident_start = if quote.is_some() { 1 } else { 0 };
+            let ident_len = rest[ident_start..].iter()
+                .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'_')
+                .count();
+            let ident = &rest[ident_start..ident_start + ident_len];
+
+            match ident.first() {
+                Some(c) if c.is_ascii_alphabetic() || *c == b'_' => {}
+                _ => continue,
+            }
+
+            let end = ident_start + ident_len;
+            let len = match quote {
+                Some(q) if rest.get(end) == Some(&q) => end + 1,
+                Some(_) => continue,
+                None => end,
+            };
+
+            trace!("Start heredoc {:?}", String::from_utf8_lossy(ident));
+            let indented = start.contains('-') || start.contains('~');
+            self.heredoc = Some((ident.to_vec(), indented));
+            return Some(start.len() + len);
+        }
+
+        None
+    }
+
+    /// Checks whether a line inside of a heredoc terminates it.
+    #[inline]
+    pub(crate) fn end_of_heredoc(&mut self, line: &[u8]) -> bool {
+        let is_end = match self.heredoc {
+            Some((ref ident, true)) => Bytes::new(line).trim().as_bytes() == &ident[..],
+            Some((ref ident, false)) => line == &ident[..],
+            None => return false,
+        };
+
+        if is_end {
+            trace!("End heredoc");
+            self.heredoc = None;
+        }
+
+        is_end
+    }
+
+    /// Checks whether the window starts a multi line comment, returning the
+    /// length of the start. Inside of a comment, only languages which allow
+    /// nesting open another one.
+    #[inline]
+    pub fn parse_multi_line_comment(&mut self, window: &[u8])
         -> Option<usize>
     {
         if self.quote.is_some() {
             return None
         }
 
+        // Nested comments only nest inside of each other, other multi line
+        // comments end at their first end.
+        let in_nested = self.stack.last()
+            .map_or(false, |&last| self.nested_comments.iter().any(|&(_, end)| end == last));
+
         let iter = self.multi_line_comments.into_iter()
                                           .chain(self.nested_comments);
         for &(start, end) in iter {
             if window.starts_with(start.as_bytes()) {
                 if self.stack.is_empty() ||
                    self.allows_nested ||
-                   self.nested_comments.contains(&(start, end))
+                   (in_nested && self.nested_comments.contains(&(start, end)))
                 {
                     self.stack.push(end);
 
-                    if log_enabled!(Trace) && self.allows_nested {
-                        trace!("Start nested {:?}", start);
+                    if log_enabled!(Trace) && self.stack.len() > 1 {
+                        trace!("Start nested {:?}, depth {}", start, self.stack.len());
                     } else {
                         trace!("Start {:?}", start);
                     }
@@ -108,9 +605,20 @@ impl SyntaxCounter {
         None
     }
 
+    /// Checks whether the window ends the current quote, returning the length
+    /// of the end. A backslash escapes the following byte, so `Some(2)` is
+    /// returned for it even outside of a quote.
     #[inline]
-    pub(crate) fn parse_end_of_quote(&mut self, window: &[u8]) -> Option<usize>
+    pub fn parse_end_of_quote(&mut self, window: &[u8]) -> Option<usize>
     {
+        if let Some(hashes) = self.raw_hashes {
+            return self.parse_end_of_raw_quote(window, hashes);
+        }
+
+        if self.quote_is_verbatim {
+            return self.parse_end_of_verbatim_quote(window);
+        }
+
         if self.quote.map_or(false, |q| window.starts_with(q.as_bytes())) {
             let quote = self.quote.take().unwrap();
             trace!("End {:?}", quote);
@@ -124,8 +632,123 @@ impl SyntaxCounter {
         }
     }
 
+    /// Checks for the start of an interpolated expression inside of a quote,
+    /// in which case the quote is suspended until the end of the expression.
     #[inline]
-    pub(crate) fn parse_end_of_multi_line(&mut self, window: &[u8])
+    pub fn parse_interpolation(&mut self, window: &[u8]) -> Option<usize> {
+        let quote = self.quote?;
+
+        if self.raw_hashes.is_some() || !self.quote_interpolates {
+            return None;
+        }
+
+        for &(start, end) in self.interpolations {
+            if window.starts_with(start.as_bytes()) {
+                trace!("Start interpolation {:?}", start);
+                self.interpolation.push(Interpolation {
+                    quote,
+                    quote_is_verbatim: self.quote_is_verbatim,
+                    start,
+                    end,
+                    depth: 0,
+                });
+                self.quote = None;
+                self.quote_is_verbatim = false;
+                return Some(start.len());
+            }
+        }
+
+        None
+    }
+
+    /// Checks for the end of the innermost interpolated expression, which
+    /// resumes the quote it was in. Blocks opened inside of the expression
+    /// with the last character of its start, like `{`, have to be closed
+    /// first.
+    #[inline]
+    pub fn parse_end_of_interpolation(&mut self, window: &[u8]) -> Option<usize> {
+        if self.quote.is_some() || !self.stack.is_empty() {
+            return None;
+        }
+
+        let end = {
+            let current = self.interpolation.last_mut()?;
+            let start = current.start.as_bytes();
+
+            if window.starts_with(&start[start.len() - 1..]) {
+                current.depth += 1;
+                return Some(1);
+            }
+
+            if !window.starts_with(current.end.as_bytes()) {
+                return None;
+            }
+
+            if current.depth > 0 {
+                current.depth -= 1;
+                return Some(current.end.len());
+            }
+
+            current.end
+        };
+
+        trace!("End interpolation {:?}", end);
+        let interpolation = self.interpolation.pop().unwrap();
+        self.quote = Some(interpolation.quote);
+        self.quote_is_verbatim = interpolation.quote_is_verbatim;
+        self.quote_interpolates = true;
+        Some(end.len())
+    }
+
+    /// Verbatim quotes have no escape character, instead a doubled closing
+    /// quote stands for the quote itself.
+    #[inline]
+    fn parse_end_of_verbatim_quote(&mut self, window: &[u8]) -> Option<usize> {
+        let quote = self.quote?.as_bytes();
+
+        if !window.starts_with(quote) {
+            return None;
+        }
+
+        if window[quote.len()..].starts_with(quote) {
+            return Some(quote.len() * 2);
+        }
+
+        trace!("End verbatim {:?}", self.quote);
+        self.quote = None;
+        self.quote_is_verbatim = false;
+        Some(quote.len())
+    }
+
+    /// Raw quotes have no escape character, and only end with their quote
+    /// followed by as many `#` as they were opened with.
+    #[inline]
+    fn parse_end_of_raw_quote(&mut self, window: &[u8], hashes: usize) -> Option<usize> {
+        let quote = self.quote?.as_bytes();
+
+        if !window.starts_with(quote) {
+            return None;
+        }
+
+        let closing_hashes = window[quote.len()..].iter()
+            .take(hashes)
+            .take_while(|&&c| c == b'#')
+            .count();
+
+        if closing_hashes < hashes {
+            return None;
+        }
+
+        trace!("End raw {:?} with {} #", self.quote, hashes);
+        self.quote = None;
+        self.raw_hashes = None;
+        Some(quote.len() + hashes)
+    }
+
+    /// Checks whether the window ends the innermost multi line comment,
+    /// returning the length of the end.
+    #[inline]
+    pub fn parse_end_of_multi_line(&mut self, window: &[u8])
         -> Option<usize>
     {
         if self.stack.last().map_or(false, |l| window.starts_with(l.as_bytes()))
@@ -134,7 +757,7 @@ impl SyntaxCounter {
             if log_enabled!(Trace) && self.stack.is_empty() {
                 trace!("End {:?}", last);
             } else {
-                trace!("End {:?}. Still in comments.", last);
+                trace!("End {:?}. Still in comments, depth {}.", last, self.stack.len());
             }
 
             Some(last.len())
@@ -144,3 +767,50 @@ impl SyntaxCounter {
     }
 }
 
+/// Whether the byte can be part of an identifier.
+fn is_identifier(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// Counts the occurrences of `word` in the line which aren't preceded or
+/// followed by another byte of an identifier.
+fn count_words(line: &[u8], word: &[u8]) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+
+    while let Some(index) = Bytes::new(&line[start..]).find(word) {
+        let begin = start + index;
+        let end = begin + word.len();
+        let joined = |c: Option<&u8>| c.map_or(false, |&c| is_identifier(c));
+
+        if !joined(begin.checked_sub(1).and_then(|i| line.get(i))) && !joined(line.get(end)) {
+            count += 1;
+        }
+
+        start = end;
+    }
+
+    count
+}
+
+/// The length of the character literal at the start of the window, like
+/// `'a'`, `'\''` or `'\u{1F600}'`, or `None` if the `'` doesn't start one.
+fn char_literal_len(window: &[u8]) -> Option<usize> {
+    let rest = &window[1..];
+
+    let len = match *rest.first()? {
+        b'\'' => return None,
+        // Escapes are short, but can be longer than one character.
+        b'\\' => 2 + rest.iter().skip(2).take(10).position(|&c| c == b'\'')?,
+        c if c < 0x80 => 1,
+        // The number of leading ones of the first byte of a UTF-8 character
+        // is its length.
+        c => (!c).leading_zeros() as usize,
+    };
+
+    if rest.get(len) == Some(&b'\'') {
+        Some(1 + len + 1)
+    } else {
+        None
+    }
+}
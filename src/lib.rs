@@ -64,14 +64,74 @@ extern crate serde_derive;
 #[cfg(feature = "io")]
 extern crate serde;
 
+#[cfg(feature = "cbor")]
+extern crate serde_cbor;
+
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
+
+#[cfg(feature = "gzip")]
+extern crate flate2;
+
+#[cfg(feature = "tar")]
+extern crate tar;
+
+#[cfg(feature = "zip")]
+extern crate zip;
+
+#[cfg(any(feature = "json", feature = "jupyter"))]
+extern crate serde_json;
+
 #[macro_use]
 mod utils;
 mod language;
 mod stats;
+mod cache;
+mod cocomo;
 mod sort;
 mod file_access;
+mod config;
+mod diff;
+mod progress;
+mod report;
+mod summary;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+mod formats;
+#[cfg(feature = "json")]
+mod ndjson;
 
-pub use file_access::FileAccess;
-pub use language::{LanguageType, Languages, Language};
-pub use stats::Stats;
+pub use cache::StatsCache;
+pub use cocomo::{CocomoEstimate, CocomoParams};
+pub use config::{ExtensionOverrides, ParseConfig, WalkConfig};
+pub use diff::{LanguageDelta, LanguageDiff};
+pub use file_access::{FileAccess, MemoryFile};
+#[cfg(feature = "gzip")]
+pub use file_access::GzFileAccess;
+#[cfg(feature = "tar")]
+pub use file_access::{tar_entries, TarEntry};
+#[cfg(feature = "zip")]
+pub use file_access::{zip_entries, ZipEntry};
+#[cfg(feature = "cbor")]
+pub use formats::{from_cbor, to_cbor};
+#[cfg(feature = "msgpack")]
+pub use formats::{from_msgpack, to_msgpack};
+#[cfg(feature = "json")]
+pub use ndjson::write_ndjson;
+pub use language::syntax;
+pub use language::{
+    count_path,
+    definitions_version,
+    extension_table,
+    parse_custom,
+    CustomLanguage,
+    LanguageType,
+    Languages,
+    Language,
+    SyntaxCounter,
+};
+pub use progress::Progress;
+pub use report::{LanguageReport, Report};
+pub use stats::{IndentStyle, LineEnding, LineKind, Stats, StatsFormat};
+pub use utils::bytes::Bytes;
 pub use sort::Sort;
+pub use summary::Summary;
@@ -71,17 +71,11 @@ fn main() -> Result<(), Box<Error>> {
             language.sort_by(sort_category)
         }
 
-        let mut languages: Vec<_> = languages.iter().collect();
-
-        match sort_category {
-            Sort::Blanks => languages.sort_by(|a, b| b.1.blanks.cmp(&a.1.blanks)),
-            Sort::Comments => languages.sort_by(|a, b| b.1.comments.cmp(&a.1.comments)),
-            Sort::Code => languages.sort_by(|a, b| b.1.code.cmp(&a.1.code)),
-            Sort::Files => languages.sort_by(|a, b| b.1.stats.len().cmp(&a.1.stats.len())),
-            Sort::Lines => languages.sort_by(|a, b| b.1.lines.cmp(&a.1.lines)),
-        }
+        let languages = languages.sort_by(sort_category, sort_category != Sort::Name);
+
+        let languages = languages.iter().map(|&(ref name, language)| (name, language));
 
-        print_results(&mut stdout, &row, languages.into_iter(), cli.files)?
+        print_results(&mut stdout, &row, languages, cli.files)?
     } else  {
         print_results(&mut stdout, &row, languages.iter(), cli.files)?
     }
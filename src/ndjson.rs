@@ -0,0 +1,103 @@
+use std::io::{self, Write};
+
+use serde_json;
+
+use language::Languages;
+use stats::{LineEnding, Stats};
+
+/// The statistics of a single file, as written by `write_ndjson`.
+#[derive(Serialize)]
+struct FileRecord<'a> {
+    language: &'a str,
+    name: &'a str,
+    blanks: usize,
+    code: usize,
+    comments: usize,
+    doc_comments: usize,
+    functions: usize,
+    todos: usize,
+    lines: usize,
+    bytes: usize,
+    max_line_length: usize,
+    generated: bool,
+    too_large: bool,
+    encoding: Option<&'a str>,
+    line_ending: Option<LineEnding>,
+}
+
+impl<'a> FileRecord<'a> {
+    fn new(language: &'a str, stats: &'a Stats) -> Self {
+        FileRecord {
+            language,
+            name: &stats.name,
+            blanks: stats.blanks,
+            code: stats.code,
+            comments: stats.comments,
+            doc_comments: stats.doc_comments,
+            functions: stats.functions,
+            todos: stats.todos,
+            lines: stats.lines,
+            bytes: stats.bytes,
+            max_line_length: stats.max_line_length,
+            generated: stats.generated,
+            too_large: stats.too_large,
+            encoding: stats.encoding,
+            line_ending: stats.line_ending,
+        }
+    }
+}
+
+/// Writes the statistics of every file as newline delimited JSON, one object
+/// per line with the name of its language, e.g.
+/// `{"language":"Rust","name":"src/main.rs","blanks":0,"code":1,...}`.
+/// Files of custom languages are written after the others.
+///
+/// ```no_run
+/// # use tokei::*;
+/// let mut languages = Languages::new();
+/// languages.get_statistics(&["."], vec![".git", "target"], None);
+///
+/// let stdout = std::io::stdout();
+/// write_ndjson(&languages, stdout.lock()).unwrap();
+/// ```
+pub fn write_ndjson<W: Write>(languages: &Languages, mut w: W) -> io::Result<()> {
+    let known = languages.iter().map(|(language, l)| (language.name(), l));
+    let custom = languages.custom().iter().map(|(name, l)| (&**name, l));
+
+    for (language, l) in known.chain(custom) {
+        for stats in &l.stats {
+            serde_json::to_writer(&mut w, &FileRecord::new(language, stats))?;
+            w.write_all(b"\n")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::{Language, LanguageType};
+
+    #[test]
+    fn one_object_per_file() {
+        let mut rust = Language::new();
+        rust.add_stat(Stats { code: 1, lines: 1, ..Stats::new("src/main.rs".to_owned()) });
+        rust.add_stat(Stats { blanks: 2, lines: 2, ..Stats::new("src/lib.rs".to_owned()) });
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+
+        let mut out = Vec::new();
+        write_ndjson(&languages, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["language"], "Rust");
+        assert_eq!(first["name"], "src/main.rs");
+        assert_eq!(first["code"], 1);
+    }
+}
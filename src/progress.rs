@@ -0,0 +1,52 @@
+use std::fmt;
+
+/// Reports the progress of `Languages` while gathering statistics, see
+/// `Languages::set_progress`. Files are parsed in parallel, so it's called
+/// from several threads at once.
+///
+/// Closures taking the path of the file implement this trait.
+///
+/// ```no_run
+/// # use tokei::*;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let count = AtomicUsize::new(0);
+/// let mut languages = Languages::new();
+/// languages.set_progress(move |path: &str| {
+///     let done = count.fetch_add(1, Ordering::Relaxed) + 1;
+///     eprint!("\r{} files, last {}", done, path);
+/// });
+///
+/// languages.get_statistics(&["."], vec![".git", "target"], None);
+/// ```
+pub trait Progress: Send + Sync {
+    /// Called once the file at `path` is done, whether or not it could be
+    /// parsed.
+    fn file_done(&self, path: &str);
+}
+
+impl<F: Fn(&str) + Send + Sync> Progress for F {
+    fn file_done(&self, path: &str) {
+        self(path)
+    }
+}
+
+/// The progress reporter of a `Languages`, if one is set.
+#[derive(Default)]
+pub(crate) struct Reporter(Option<Box<dyn Progress>>);
+
+impl Reporter {
+    pub(crate) fn new<P: Progress + 'static>(progress: P) -> Self {
+        Reporter(Some(Box::new(progress)))
+    }
+
+    pub(crate) fn get(&self) -> Option<&dyn Progress> {
+        self.0.as_deref()
+    }
+}
+
+impl fmt::Debug for Reporter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
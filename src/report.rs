@@ -0,0 +1,122 @@
+use language::{Language, Languages};
+use stats::Stats;
+use summary::Summary;
+
+/// The results of counting as a whole, with the totals of every language, the
+/// totals of each language and the statistics of each file. Unlike
+/// `Languages`, its shape doesn't depend on how the results are stored, so
+/// it's a stable format to serialize for other tools.
+///
+/// ```no_run
+/// # use tokei::*;
+/// let mut languages = Languages::new();
+/// languages.get_statistics(&["."], vec![".git", "target"], None);
+///
+/// let report = Report::from(&languages).without_files();
+/// println!("{} lines in {} languages", report.total.lines, report.languages.len());
+/// ```
+#[cfg_attr(feature = "io", derive(Serialize))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// The totals of every language, including custom ones.
+    pub total: Summary,
+    /// The languages which have any lines, files or skipped files, with the
+    /// languages of tokei in the order of `LanguageType` followed by the
+    /// custom languages in the order of their names.
+    pub languages: Vec<LanguageReport>,
+}
+
+/// The results of a single language in a `Report`.
+#[cfg_attr(feature = "io", derive(Serialize))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LanguageReport {
+    /// The name of the language, as given by `LanguageType::name`, or the
+    /// name of the custom language.
+    pub name: String,
+    /// The totals of the language.
+    pub summary: Summary,
+    /// Whether the language had problems with parsing files.
+    pub inaccurate: bool,
+    /// Number of files of the language which couldn't be parsed.
+    pub skipped: usize,
+    /// The statistics of each file, unless left out with
+    /// `Report::without_files`.
+    pub files: Vec<Stats>,
+}
+
+impl Report {
+    /// Leaves out the statistics of each file, keeping only the totals.
+    pub fn without_files(mut self) -> Self {
+        for language in &mut self.languages {
+            language.files.clear();
+        }
+
+        self
+    }
+}
+
+impl<'a> From<&'a Languages> for Report {
+    fn from(languages: &'a Languages) -> Self {
+        let custom = languages.custom().iter().map(|(name, language)| (name.clone(), language));
+
+        Report {
+            total: languages.summary(),
+            languages: languages.iter()
+                .map(|(language_type, language)| (language_type.name().to_owned(), language))
+                .chain(custom)
+                .filter(|(_, language)| !language.is_empty() || language.has_files())
+                .map(|(name, language)| LanguageReport::new(name, language))
+                .collect(),
+        }
+    }
+}
+
+impl LanguageReport {
+    fn new(name: String, language: &Language) -> Self {
+        LanguageReport {
+            name,
+            summary: language.summary(),
+            inaccurate: language.inaccurate,
+            skipped: language.skipped,
+            files: language.stats.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::LanguageType;
+
+    #[test]
+    fn from_languages() {
+        let mut rust = Language::new();
+        rust.add_stat(Stats { code: 6, blanks: 2, ..Stats::new("a.rs".to_owned()) });
+        rust.add_stat(Stats::new("empty.rs".to_owned()));
+        rust.total();
+
+        let mut c = Language::new();
+        c.add_stat(Stats { code: 2, ..Stats::new("main.c".to_owned()) });
+        c.total();
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, c);
+        languages.insert(LanguageType::Python, Language::new());
+
+        let report = Report::from(&languages);
+        assert_eq!(report.total, languages.summary());
+        assert_eq!(report.total.lines, 10);
+
+        let names = report.languages.iter().map(|l| &*l.name).collect::<Vec<_>>();
+        assert_eq!(names, ["C", "Rust"]);
+
+        let rust = &report.languages[1];
+        assert_eq!((rust.summary.code, rust.summary.files), (6, 2));
+        assert_eq!(rust.files.len(), 2);
+
+        let report = report.without_files();
+        assert!(report.languages.iter().all(|l| l.files.is_empty()));
+        assert_eq!(report.languages[1].summary.files, 2);
+    }
+}
@@ -14,6 +14,8 @@ pub enum Sort {
     Files,
     /// Sort by number of lines.
     Lines,
+    /// Sort by name.
+    Name,
 }
 
 impl FromStr for Sort {
@@ -26,6 +28,7 @@ impl FromStr for Sort {
             "code" => Sort::Code,
             "files" => Sort::Files,
             "lines" => Sort::Lines,
+            "name" => Sort::Name,
             s => return Err(format!("Unsupported sorting option: {}", s))
         })
     }
@@ -1,8 +1,21 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+
+use memchr;
+
+use language::LanguageType;
+#[cfg(feature = "io")]
+use utils::bytes;
 
 /// A struct representing the statistics of a file.
+///
+/// Two `Stats` are equal if all of their fields are, including `name`. Use
+/// `counts_eq` to only compare the counts.
 #[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Stats {
     /// Number of blank lines within the file.
     pub blanks: usize,
@@ -11,10 +24,62 @@ pub struct Stats {
     /// Number of comments within the file. (_includes both multi line, and
     /// single line comments_)
     pub comments: usize,
+    /// Number of documentation comments within the file. These lines are
+    /// also included in `comments`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub doc_comments: usize,
+    /// Number of lines of code which start a function or other definition,
+    /// for languages that have `function_patterns`. This is a heuristic, see
+    /// `LanguageType::function_patterns`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub functions: usize,
+    /// Number of markers like `TODO` or `FIXME` within comments, see
+    /// `ParseConfig::todo_markers`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub todos: usize,
     /// Total number of lines within the file.
     pub lines: usize,
+    /// Number of bytes read from the file, before any decoding.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub bytes: usize,
+    /// Length in bytes of the longest line within the file, where each tab
+    /// counts up to the next tab stop of `ParseConfig::tab_width`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub max_line_length: usize,
+    /// Number of lines in the longest run of consecutive blank lines within
+    /// the file.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub max_blank_run: usize,
+    /// Whether the file was recognised as generated and its lines weren't
+    /// counted, see `ParseConfig::skip_generated`.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub generated: bool,
+    /// Whether the file was larger than `ParseConfig::max_file_bytes` and
+    /// wasn't read, so that none of its lines or bytes were counted.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub too_large: bool,
+    /// The indentation used by most of the indented lines of code, or `None`
+    /// if no line of code is indented. Combined statistics only have one if
+    /// all of their files use the same indentation.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub indent: Option<IndentStyle>,
+    /// Name of the encoding the file was decoded from, like `UTF-8`,
+    /// `UTF-16LE` or `ISO-8859-1`, or `None` if it isn't known, like for text
+    /// streamed by `LanguageType::parse_from_reader`. Combined statistics only
+    /// have one if all of their files were decoded from the same encoding.
+    #[cfg_attr(feature = "io", serde(default, deserialize_with = "deserialize_encoding"))]
+    pub encoding: Option<EncodingName>,
+    /// The line endings used within the file, or `None` if it has no line
+    /// endings at all.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub line_ending: Option<LineEnding>,
     /// File name.
     pub name: String,
+    /// Statistics of blocks of other languages embedded in the file, like
+    /// `<script>` tags in HTML. Lines in these blocks are not included in the
+    /// other counts.
+    #[cfg_attr(feature = "io", serde(default))]
+    pub children: BTreeMap<LanguageType, Vec<Stats>>,
 }
 
 impl Stats {
@@ -24,8 +89,284 @@ impl Stats {
             blanks: 0,
             code: 0,
             comments: 0,
+            doc_comments: 0,
+            functions: 0,
+            todos: 0,
             lines: 0,
+            bytes: 0,
+            max_line_length: 0,
+            max_blank_run: 0,
+            generated: false,
+            too_large: false,
+            indent: None,
+            encoding: None,
+            line_ending: None,
             name,
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the counts of both are equal, including the counts of their
+    /// children, ignoring the names of the files.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let a = LanguageType::Rust.parse_from_str("a.rs".into(), "fn main() {}\n");
+    /// let b = LanguageType::Rust.parse_from_str("b.rs".into(), "fn main() {}\n");
+    ///
+    /// assert!(a.counts_eq(&b));
+    /// assert_ne!(a, b);
+    /// ```
+    pub fn counts_eq(&self, other: &Stats) -> bool {
+        self.without_names() == other.without_names()
+    }
+
+    fn without_names(&self) -> Stats {
+        let children = self.children.iter()
+            .map(|(&language, children)| {
+                (language, children.iter().map(Stats::without_names).collect())
+            })
+            .collect();
+
+        Stats {
+            name: String::new(),
+            children,
+            ..self.clone()
+        }
+    }
+
+    /// The fraction of lines which are code, or `0.0` if there are no lines.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// let stats = Stats { code: 3, lines: 4, ..Stats::new(String::new()) };
+    /// assert_eq!(stats.code_ratio(), 0.75);
+    /// assert_eq!(Stats::new(String::new()).code_ratio(), 0.0);
+    /// ```
+    pub fn code_ratio(&self) -> f64 {
+        ratio(self.code, self.lines)
+    }
+
+    /// The fraction of lines which are comments, or `0.0` if there are no
+    /// lines.
+    pub fn comment_ratio(&self) -> f64 {
+        ratio(self.comments, self.lines)
+    }
+
+    /// The fraction of lines which are blank, or `0.0` if there are no lines.
+    pub fn blank_ratio(&self) -> f64 {
+        ratio(self.blanks, self.lines)
+    }
+}
+
+/// The name of an encoding. Spelled as an alias so that serde doesn't borrow
+/// it from the input, which would require the input to be `'static`.
+type EncodingName = &'static str;
+
+/// Deserializes the name of an encoding as the name `bytes::decode`
+/// returns for it, since names can't be borrowed from the input for good.
+#[cfg(feature = "io")]
+fn deserialize_encoding<'de, D>(deserializer: D) -> Result<Option<&'static str>, D::Error>
+    where D: serde::Deserializer<'de>
+{
+    let name: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(name.and_then(|name| bytes::encoding_name(&name)))
+}
+
+impl AddAssign for Stats {
+    fn add_assign(&mut self, rhs: Self) {
+        // The indentation and encoding are only kept if both sides agree,
+        // where a side without any bytes, like the start of a sum, always does.
+        let (indent, encoding) = match (self.bytes, rhs.bytes) {
+            (0, _) => (rhs.indent, rhs.encoding),
+            (_, 0) => (self.indent, self.encoding),
+            _ => (
+                self.indent.filter(|&indent| Some(indent) == rhs.indent),
+                self.encoding.filter(|&encoding| Some(encoding) == rhs.encoding),
+            ),
+        };
+
+        self.indent = indent;
+        self.encoding = encoding;
+
+        self.blanks += rhs.blanks;
+        self.code += rhs.code;
+        self.comments += rhs.comments;
+        self.doc_comments += rhs.doc_comments;
+        self.functions += rhs.functions;
+        self.todos += rhs.todos;
+        self.lines += rhs.lines;
+        self.bytes += rhs.bytes;
+        self.max_line_length = self.max_line_length.max(rhs.max_line_length);
+        self.max_blank_run = self.max_blank_run.max(rhs.max_blank_run);
+        self.generated |= rhs.generated;
+        self.too_large |= rhs.too_large;
+        self.line_ending = LineEnding::combine(self.line_ending, rhs.line_ending);
+
+        if self.name.is_empty() {
+            self.name = rhs.name;
+        }
+
+        for (language, children) in rhs.children {
+            self.children.entry(language).or_default().extend(children);
+        }
+    }
+}
+
+/// Combines the statistics of two files, summing their counts. The name of
+/// the left-hand side is kept, unless it is empty.
+///
+/// ```
+/// # use tokei::*;
+/// let a = Stats { code: 2, lines: 2, ..Stats::new("a.rs".to_owned()) };
+/// let b = Stats { blanks: 1, lines: 1, ..Stats::new("b.rs".to_owned()) };
+///
+/// let total: Stats = vec![a, b].into_iter().sum();
+/// assert_eq!(total.name, "a.rs");
+/// assert_eq!((total.code, total.blanks, total.lines), (2, 1, 3));
+/// ```
+impl Add for Stats {
+    type Output = Stats;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl Sum for Stats {
+    fn sum<I: Iterator<Item = Stats>>(iter: I) -> Self {
+        iter.fold(Stats::new(String::new()), Add::add)
+    }
+}
+
+/// Divides `count` by `lines`, returning `0.0` instead of `NaN` when there are
+/// no lines.
+pub(crate) fn ratio(count: usize, lines: usize) -> f64 {
+    if lines == 0 {
+        0.0
+    } else {
+        count as f64 / lines as f64
+    }
+}
+
+/// The classification of a single line of a file.
+#[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LineKind {
+    /// The line contains code.
+    Code,
+    /// The line only contains comments.
+    Comment,
+    /// The line only contains whitespace.
+    Blank,
+}
+
+/// The indentation of the lines of a file, see `Stats::indent`.
+#[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IndentStyle {
+    /// Lines are indented with tabs.
+    Tabs,
+    /// Lines are indented with spaces, each level being this many of them.
+    Spaces(u8),
+}
+
+/// The line endings of a file, see `Stats::line_ending`.
+#[cfg_attr(feature = "io", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LineEnding {
+    /// Lines end with `\n`.
+    Lf,
+    /// Lines end with `\r\n`.
+    Crlf,
+    /// Lines end with a lone `\r`.
+    Cr,
+    /// Lines end with more than one of these.
+    Mixed,
+}
+
+impl LineEnding {
+    /// The line endings used within `text`, or `None` if it has none.
+    pub(crate) fn detect(text: &[u8]) -> Option<Self> {
+        let mut ending = None;
+        let mut positions = memchr::memchr2_iter(b'\n', b'\r', text);
+
+        while let Some(i) = positions.next() {
+            let this = match (text[i], text.get(i + 1)) {
+                (b'\n', _) => LineEnding::Lf,
+                (_, Some(b'\n')) => {
+                    positions.next();
+                    LineEnding::Crlf
+                }
+                _ => LineEnding::Cr,
+            };
+
+            ending = LineEnding::combine(ending, Some(this));
+
+            if ending == Some(LineEnding::Mixed) {
+                break;
+            }
+        }
+
+        ending
+    }
+
+    /// The line endings of text made up of text with `a` and `b` as theirs.
+    pub(crate) fn combine(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (None, ending) | (ending, None) => ending,
+            (Some(a), Some(b)) if a == b => Some(a),
+            _ => Some(LineEnding::Mixed),
+        }
+    }
+}
+
+/// Tallies the leading whitespace of lines of code to find their
+/// `IndentStyle`.
+#[derive(Debug, Default)]
+pub(crate) struct IndentCounter {
+    tabs: usize,
+    spaces: usize,
+    /// Number of times the indentation grew by each number of spaces.
+    steps: BTreeMap<u8, usize>,
+    /// Number of spaces indenting the previous line of code.
+    previous: usize,
+}
+
+impl IndentCounter {
+    /// Adds a line of code, including its leading whitespace.
+    pub(crate) fn add(&mut self, line: &[u8]) {
+        match line.first() {
+            Some(b'\t') => {
+                self.tabs += 1;
+                self.previous = 0;
+            }
+            Some(b' ') => {
+                let width = line.iter().take_while(|&&b| b == b' ').count();
+                self.spaces += 1;
+
+                if width > self.previous && width - self.previous <= u8::MAX as usize {
+                    *self.steps.entry((width - self.previous) as u8).or_insert(0) += 1;
+                }
+
+                self.previous = width;
+            }
+            _ => self.previous = 0,
+        }
+    }
+
+    /// The most common indentation, preferring spaces when as many lines use
+    /// tabs, and the smaller width when as many steps use another.
+    pub(crate) fn style(&self) -> Option<IndentStyle> {
+        if self.tabs == 0 && self.spaces == 0 {
+            None
+        } else if self.tabs > self.spaces {
+            Some(IndentStyle::Tabs)
+        } else {
+            self.steps.iter()
+                .max_by_key(|&(&step, &count)| (count, Reverse(step)))
+                .map(|(&step, _)| IndentStyle::Spaces(step))
         }
     }
 }
@@ -39,33 +380,259 @@ fn find_char_boundary(s: &str, index: usize) -> usize {
     unreachable!();
 }
 
+/// Formats `n` with `separator` between each group of three digits, if any.
+fn format_number(n: usize, separator: Option<char>) -> String {
+    let digits = n.to_string();
+
+    let separator = match separator {
+        Some(separator) => separator,
+        None => return digits,
+    };
+
+    // The leading group has one to three digits, every following group three.
+    let first = (digits.len() - 1) % 3 + 1;
+    let mut formatted = String::from(&digits[..first]);
+
+    for group in digits.as_bytes()[first..].chunks(3) {
+        formatted.push(separator);
+        formatted.extend(group.iter().map(|&b| b as char));
+    }
+
+    formatted
+}
+
 macro_rules! display_stats {
-    ($f:expr, $this:expr, $name:expr, $max:expr) => {
+    ($f:expr, $this:expr, $name:expr, $max:expr, $format:expr) => {{
+        let num = |n| format_number(n, $format.thousands_separator);
+
         write!($f,
-               " {: <max$} {:>12} {:>12} {:>12} {:>12}",
+               " {: <max$} {:>width$} {:>width$} {:>width$} {:>width$}",
                $name,
-               $this.lines,
-               $this.code,
-               $this.comments,
-               $this.blanks,
-               max = $max)
+               num($this.lines),
+               num($this.code),
+               num($this.comments),
+               num($this.blanks),
+               max = $max,
+               width = $format.num_width)?;
+
+        if $f.alternate() {
+            write!($f, " {:>width$}", num($this.doc_comments), width = $format.num_width)?;
+        }
+
+        Ok(())
+    }}
+}
+
+/// The widths of the columns used when formatting `Stats` as a row of a
+/// table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StatsFormat {
+    /// Width of the file name column. Longer names are truncated from the
+    /// start. Defaults to `25`.
+    pub name_width: usize,
+    /// Width of each of the numeric columns. Defaults to `12`. Numbers
+    /// which don't fit, including their separators, push the following
+    /// columns out of alignment, so this may need to be widened when using
+    /// `thousands_separator`.
+    pub num_width: usize,
+    /// Separator inserted between each group of three digits, e.g. `,` to
+    /// write `1,234,567`. Defaults to `None`.
+    pub thousands_separator: Option<char>,
+}
+
+impl Default for StatsFormat {
+    fn default() -> Self {
+        StatsFormat {
+            name_width: 25,
+            num_width: 12,
+            thousands_separator: None,
+        }
     }
 }
 
-impl fmt::Display for Stats {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Stats {
+    /// Formats the stats as a row of a table like `Display`, with the column
+    /// widths of `format`.
+    ///
+    /// ```
+    /// # use tokei::*;
+    /// # use std::fmt;
+    /// struct Row<'a>(&'a Stats, StatsFormat);
+    ///
+    /// impl<'a> fmt::Display for Row<'a> {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         self.0.fmt_with(f, self.1)
+    ///     }
+    /// }
+    ///
+    /// let stats = Stats { code: 1234567, lines: 1234567, ..Stats::new("src/main.rs".to_owned()) };
+    ///
+    /// let wide = StatsFormat { num_width: 15, ..StatsFormat::default() };
+    /// assert_eq!(Row(&stats, wide).to_string().len(), 1 + 25 + 4 * 16);
+    ///
+    /// let narrow = StatsFormat { name_width: 8, ..StatsFormat::default() };
+    /// assert!(Row(&stats, narrow).to_string().starts_with(" |main.rs "));
+    ///
+    /// let separated = StatsFormat { thousands_separator: Some(','), ..StatsFormat::default() };
+    /// let row = Row(&stats, separated).to_string();
+    /// assert!(row.contains("   1,234,567    1,234,567            0"));
+    /// assert_eq!(row.len(), Row(&stats, StatsFormat::default()).to_string().len());
+    /// ```
+    pub fn fmt_with(&self, f: &mut fmt::Formatter, format: StatsFormat) -> fmt::Result {
         let name_length = self.name.len();
+        let max_len = format.name_width;
 
-        let max_len = f.width().unwrap_or(25);
-
-        if name_length <= max_len {
-            display_stats!(f, self, self.name, max_len)
+        // A width with no room for the '|' and part of the name doesn't
+        // truncate it.
+        if name_length <= max_len || max_len < 2 {
+            display_stats!(f, self, self.name, max_len, format)
         } else {
             let mut formatted = String::from("|");
             // Add 1 to the index to account for the '|' we add to the output string
             let from = find_char_boundary(&self.name, name_length + 1 - max_len);
             formatted.push_str(&self.name[from..]);
-            display_stats!(f, self, formatted, max_len)
+            display_stats!(f, self, formatted, max_len, format)
+        }
+    }
+}
+
+/// Formats the stats as a row of a table. The alternate flag (`{:#}`) adds a
+/// trailing column containing the number of documentation comments. The
+/// width (`{:30}`) sets the width of the file name column.
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut format = StatsFormat::default();
+
+        if let Some(width) = f.width() {
+            format.name_width = width;
         }
+
+        self.fmt_with(f, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thousands_separator() {
+        assert_eq!(format_number(1234567, None), "1234567");
+        assert_eq!(format_number(0, Some(',')), "0");
+        assert_eq!(format_number(999, Some(',')), "999");
+        assert_eq!(format_number(1000, Some(',')), "1,000");
+        assert_eq!(format_number(123456, Some('_')), "123_456");
+        assert_eq!(format_number(1234567, Some(',')), "1,234,567");
+    }
+
+    #[test]
+    fn narrow_name_width() {
+        struct Row<'a>(&'a Stats, StatsFormat);
+
+        impl<'a> fmt::Display for Row<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt_with(f, self.1)
+            }
+        }
+
+        let stats = Stats::new("src/main.rs".to_owned());
+
+        for &name_width in &[0, 1] {
+            let format = StatsFormat { name_width, ..StatsFormat::default() };
+            assert!(Row(&stats, format).to_string().starts_with(" src/main.rs "));
+        }
+
+        let format = StatsFormat { name_width: 2, ..StatsFormat::default() };
+        assert!(Row(&stats, format).to_string().starts_with(" |s "));
+    }
+
+    #[test]
+    fn counts_eq() {
+        let text = b"<script>\n// a\nlet a = 1;\n</script>\n";
+        let a = LanguageType::Html.parse_from_bytes("a.html".into(), text).unwrap();
+        let b = LanguageType::Html.parse_from_bytes("b.html".into(), text).unwrap();
+        assert!(!a.children.is_empty());
+
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+        assert!(a.counts_eq(&b));
+
+        let c = Stats { code: a.code + 1, ..a.clone() };
+        assert!(!a.counts_eq(&c));
+    }
+
+    #[test]
+    fn indent() {
+        let indent = |text: &str| LanguageType::Rust.parse_from_str("a.rs".into(), text).indent;
+
+        assert_eq!(indent("fn main() {}\n"), None);
+        // Comments and blank lines don't count.
+        assert_eq!(indent("fn main() {\n\t// a\n  \n}\n"), None);
+        assert_eq!(indent("fn main() {\n\tlet a = 1;\n}\n"), Some(IndentStyle::Tabs));
+
+        let text = "mod a {\n    fn b() {\n        c();\n        d();\n    }\n}\n";
+        assert_eq!(indent(text), Some(IndentStyle::Spaces(4)));
+
+        // The most common step wins, even if lines start at other widths.
+        let text = "fn a() {\n  b(\n      c,\n  );\n  d(\n    e,\n  );\n}\n";
+        assert_eq!(indent(text), Some(IndentStyle::Spaces(2)));
+
+        // Mixed files go with the majority.
+        let text = "fn a() {\n\tb();\n\tc();\n    d();\n}\n";
+        assert_eq!(indent(text), Some(IndentStyle::Tabs));
+
+        let stats = LanguageType::Rust.parse_from_reader("a.rs".into(), text.as_bytes()).unwrap();
+        assert_eq!(stats.indent, Some(IndentStyle::Tabs));
+
+        // Combined statistics only keep an indentation their files agree on.
+        let spaces = LanguageType::Rust.parse_from_str("b.rs".into(), "fn b() {\n    c();\n}\n");
+        assert_eq!((stats.clone() + stats.clone()).indent, Some(IndentStyle::Tabs));
+        assert_eq!((stats.clone() + spaces.clone() + stats).indent, None);
+    }
+
+    #[test]
+    fn combined_encoding() {
+        let utf8 = LanguageType::Rust.parse_from_bytes("a.rs".into(), b"fn a() {}\n").unwrap();
+        let utf16 = LanguageType::Rust.parse_from_bytes("b.rs".into(), b"\xff\xfex\x00\n\x00").unwrap();
+        assert_eq!(utf16.encoding, Some("UTF-16LE"));
+
+        let encoding = |files: &[&Stats]| files.iter().map(|&stats| stats.clone()).sum::<Stats>().encoding;
+        assert_eq!(encoding(&[&utf8, &utf8]), Some("UTF-8"));
+        assert_eq!(encoding(&[&utf8, &utf16]), None);
+        assert_eq!(encoding(&[&utf8, &utf16, &utf8]), None);
+    }
+
+    #[test]
+    fn line_ending() {
+        let ending = |text: &str| {
+            let stats = LanguageType::Rust.parse_from_str("a.rs".into(), text);
+            let streamed = LanguageType::Rust.parse_from_reader("a.rs".into(), text.as_bytes());
+            assert_eq!(streamed.unwrap().line_ending, stats.line_ending);
+            stats.line_ending
+        };
+
+        assert_eq!(ending("fn a() {}\n\nfn b() {}\n"), Some(LineEnding::Lf));
+        assert_eq!(ending("fn a() {}\r\n\r\nfn b() {}"), Some(LineEnding::Crlf));
+        assert_eq!(ending("fn a() {}\rfn b() {}\r"), Some(LineEnding::Cr));
+        assert_eq!(ending("fn a() {}\r\nfn b() {}\n"), Some(LineEnding::Mixed));
+        assert_eq!(ending("fn a() {}\r\nfn b() {}\r"), Some(LineEnding::Mixed));
+        assert_eq!(ending("fn a() {}"), None);
+        assert_eq!(ending(""), None);
+    }
+
+    #[test]
+    fn max_blank_run() {
+        let run = |text: &str| {
+            let stats = LanguageType::Rust.parse_from_str("a.rs".into(), text);
+            let streamed = LanguageType::Rust.parse_from_reader("a.rs".into(), text.as_bytes());
+            assert_eq!(streamed.unwrap().max_blank_run, stats.max_blank_run);
+            stats.max_blank_run
+        };
+
+        assert_eq!(run(""), 0);
+        assert_eq!(run("fn a() {}\n// b\n"), 0);
+        assert_eq!(run("fn a() {}\n\n\n// b\n\nfn c() {}\n"), 2);
+        // Runs at the start and end of the file count too.
+        assert_eq!(run("\nfn a() {}\n\n  \n\t\n"), 3);
     }
 }
@@ -0,0 +1,100 @@
+use language::Language;
+use stats;
+
+/// The totals of one or more languages, with the share of each kind of line,
+/// as created by `Language::summary` and `Languages::summary`.
+///
+/// ```
+/// # use tokei::*;
+/// let rust = Language { code: 3, blanks: 1, lines: 4, ..Language::new() };
+/// let summary = rust.summary();
+///
+/// assert_eq!(summary.lines, 4);
+/// assert_eq!(summary.code_percentage, 75.0);
+/// assert_eq!(summary.comment_percentage, 0.0);
+/// ```
+#[cfg_attr(feature = "io", derive(Serialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    /// Number of blank lines.
+    pub blanks: usize,
+    /// Number of lines of code.
+    pub code: usize,
+    /// Number of comments.
+    pub comments: usize,
+    /// Number of total lines.
+    pub lines: usize,
+    /// Number of files.
+    pub files: usize,
+    /// Length in bytes of the longest line across all files.
+    pub max_line_length: usize,
+    /// Percentage of lines which are code, or `0.0` if there are no lines.
+    pub code_percentage: f64,
+    /// Percentage of lines which are comments, or `0.0` if there are no
+    /// lines.
+    pub comment_percentage: f64,
+    /// Percentage of lines which are blank, or `0.0` if there are no lines.
+    pub blank_percentage: f64,
+}
+
+impl Summary {
+    pub(crate) fn new<'a, I>(languages: I) -> Self
+        where I: IntoIterator<Item = &'a Language>
+    {
+        let mut summary = Summary::default();
+
+        for language in languages {
+            summary.blanks += language.blanks;
+            summary.code += language.code;
+            summary.comments += language.comments;
+            summary.lines += language.lines;
+            summary.files += language.file_count();
+            summary.max_line_length = summary.max_line_length.max(language.max_line_length);
+        }
+
+        summary.code_percentage = percentage(summary.code, summary.lines);
+        summary.comment_percentage = percentage(summary.comments, summary.lines);
+        summary.blank_percentage = percentage(summary.blanks, summary.lines);
+        summary
+    }
+}
+
+fn percentage(count: usize, lines: usize) -> f64 {
+    stats::ratio(count, lines) * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::{LanguageType, Languages};
+    use stats::Stats;
+
+    #[test]
+    fn grand_total() {
+        let mut rust = Language::new();
+        let a = Stats { code: 6, blanks: 2, max_line_length: 80, ..Stats::new("a.rs".to_owned()) };
+        rust.add_stat(a);
+        rust.add_stat(Stats { comments: 2, max_line_length: 20, ..Stats::new("b.rs".to_owned()) });
+        rust.total();
+
+        let mut c = Language::new();
+        c.add_stat(Stats { code: 10, max_line_length: 100, ..Stats::new("main.c".to_owned()) });
+        c.total();
+
+        let mut languages = Languages::new();
+        languages.insert(LanguageType::Rust, rust);
+        languages.insert(LanguageType::C, c);
+
+        let summary = languages[&LanguageType::Rust].summary();
+        assert_eq!((summary.lines, summary.files, summary.max_line_length), (10, 2, 80));
+        assert_eq!(summary.code_percentage, 60.0);
+        assert_eq!(summary.comment_percentage, 20.0);
+        assert_eq!(summary.blank_percentage, 20.0);
+
+        let summary = languages.summary();
+        assert_eq!((summary.lines, summary.files, summary.max_line_length), (20, 3, 100));
+        assert_eq!(summary.code_percentage, 80.0);
+
+        assert_eq!(Languages::new().summary(), Summary::default());
+    }
+}
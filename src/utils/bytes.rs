@@ -3,10 +3,11 @@ use std::vec;
 use std::borrow::Cow;
 use std::error;
 
-use encoding_rs::Encoding;
+use encoding_rs::{Encoding, UTF_8};
 use memchr;
 
-const FIRST_FEW_BYTES: usize = 8000;
+/// Default number of bytes checked by `is_binary`.
+pub const FIRST_FEW_BYTES: usize = 8000;
 
 #[derive(Debug)]
 pub enum DecodingError {
@@ -25,21 +26,89 @@ impl fmt::Display for DecodingError {
     }
 }
 
-/// Test if file is a binary file by checking if any of `FIRST_FEW_BYTES` first bytes are null
-/// bytes (`0x00`).
+/// Percentage of the sniffed bytes which have to be non-text for a file containing null bytes to
+/// be considered binary.
+const BINARY_PERCENTAGE: usize = 30;
+
+/// Test if file is a binary file by checking the `FIRST_FEW_BYTES` first bytes, see
+/// `is_binary_with`.
 pub fn is_binary(bytes: &[u8]) -> bool {
+    is_binary_with(bytes, FIRST_FEW_BYTES, false)
+}
+
+/// Test if file is a binary file by checking the `sniff_bytes` first bytes. If `allow_wide_bom` is
+/// set, files starting with a UTF-16 or UTF-32 BOM are never considered binary.
+///
+/// Files without null bytes (`0x00`) are always text. Otherwise a file is binary if more than 30%
+/// of the checked bytes are control characters or invalid UTF-8, so a stray null byte in a source
+/// file doesn't cause it to be skipped.
+pub fn is_binary_with(bytes: &[u8], sniff_bytes: usize, allow_wide_bom: bool) -> bool {
+    if allow_wide_bom && has_wide_bom(bytes) {
+        return false;
+    }
+
     // This mimics the git test that does the same from here:
     // https://github.com/git/git/blob/2d3b1c576c85b7f5db1f418907af00ab88e0c303/xdiff-interface.c#L202
-    let end = usize::min(FIRST_FEW_BYTES, bytes.len());
-    memchr::memchr(0u8, &bytes[..end]).is_some()
+    let end = usize::min(sniff_bytes, bytes.len());
+    let bytes = &bytes[..end];
+
+    if memchr::memchr(0u8, bytes).is_none() {
+        return false;
+    }
+
+    non_text_bytes(bytes) * 100 > bytes.len() * BINARY_PERCENTAGE
 }
 
+/// Count the control characters, other than common whitespace and escapes, and the bytes which
+/// aren't valid UTF-8.
+fn non_text_bytes(bytes: &[u8]) -> usize {
+    let control = bytes.iter()
+        .filter(|&&b| (b < 0x20 && !b"\t\n\r\x0c\x1b".contains(&b)) || b == 0x7f)
+        .count();
+
+    let mut invalid = 0;
+    let mut rest = bytes;
+
+    while let Err(error) = ::std::str::from_utf8(rest) {
+        // a sequence cut off at the end of the checked bytes is not invalid.
+        let len = match error.error_len() {
+            Some(len) => len,
+            None => break,
+        };
+
+        invalid += len;
+        rest = &rest[error.valid_up_to() + len..];
+    }
+
+    control + invalid
+}
+
+/// Test if the bytes start with a UTF-16 or UTF-32 byte order mark.
+pub fn has_wide_bom(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\x00\x00\xfe\xff") ||
+    bytes.starts_with(b"\xff\xfe") ||
+    bytes.starts_with(b"\xfe\xff")
+}
+
+/// Strips a leading UTF-8 byte order mark, if present.
+pub fn strip_utf8_bom(bytes: &[u8]) -> &[u8] {
+    if bytes.starts_with(b"\xef\xbb\xbf") { &bytes[3..] } else { bytes }
+}
+
+/// Name of the encoding that `decode` falls back to for text which isn't
+/// valid UTF-8.
+pub const LATIN_1: &str = "ISO-8859-1";
+
 /// Do your best to try and construct a Bytes instance while performing as much detection as
 /// possible.
 ///
 /// This looks at:
-/// * The BOM of the file, if present.
-pub fn decode<'a>(bytes: &'a [u8]) -> Result<Cow<'a, [u8]>, DecodingError> {
+/// * The BOM of the file, if present, which is stripped, transcoding UTF-16 to UTF-8.
+/// * Whether the file is valid UTF-8, otherwise it is treated as Latin-1.
+///
+/// Returns the name of the detected encoding alongside, like `UTF-8`,
+/// `UTF-16LE` or `ISO-8859-1`.
+pub fn decode<'a>(bytes: &'a [u8]) -> Result<(Cow<'a, [u8]>, &'static str), DecodingError> {
     if bytes.len() >= 2 {
         let end = usize::min(3, bytes.len());
 
@@ -50,18 +119,35 @@ pub fn decode<'a>(bytes: &'a [u8]) -> Result<Cow<'a, [u8]>, DecodingError> {
             };
 
             match out {
-                Cow::Borrowed(s) => return Ok(Cow::from(s.as_bytes())),
-                Cow::Owned(s) => return Ok(Cow::from(s.into_bytes())),
+                Cow::Borrowed(s) => return Ok((Cow::from(s.as_bytes()), encoding.name())),
+                Cow::Owned(s) => return Ok((Cow::from(s.into_bytes()), encoding.name())),
             }
         }
     }
 
     // TODO: look for encoding comments in the first 5 (ish) lines.
 
-    // encoding could not be detected, pass through.
-    Ok(Cow::Borrowed(bytes))
+    if ::std::str::from_utf8(bytes).is_ok() {
+        return Ok((Cow::Borrowed(bytes), UTF_8.name()));
+    }
+
+    // not valid UTF-8, every byte maps directly to a Latin-1 code point.
+    let out = bytes.iter().map(|&b| b as char).collect::<String>();
+    Ok((Cow::from(out.into_bytes()), LATIN_1))
+}
+
+/// The name returned by `decode` for the encoding named
+/// `name`, if it's one that can be detected.
+#[cfg(feature = "io")]
+pub(crate) fn encoding_name(name: &str) -> Option<&'static str> {
+    if name == LATIN_1 {
+        return Some(LATIN_1);
+    }
+
+    Encoding::for_label(name.as_bytes()).map(Encoding::name)
 }
 
+/// A line, or the whole text, of a file being parsed.
 #[derive(Clone, Copy)]
 pub struct Bytes<'a> {
     bytes: &'a [u8],
@@ -79,6 +165,11 @@ impl<'a> Bytes<'a> {
         self.bytes.len()
     }
 
+    /// Check if there are no bytes contained.
+    pub fn is_empty(self) -> bool {
+        self.bytes.is_empty()
+    }
+
     /// Treat as a bytes slice.
     pub fn as_bytes(self) -> &'a [u8] {
         self.bytes
@@ -102,6 +193,36 @@ impl<'a> Bytes<'a> {
         }
     }
 
+    /// Check if the bytes only contain whitespace, as identified by using
+    /// `char::is_whitespace` on `utf8_chars_lossy`.
+    ///
+    /// ASCII bytes are checked directly, only decoding the rest of the bytes
+    /// once a non-ASCII byte is found.
+    pub fn is_whitespace(self) -> bool {
+        let end = self.bytes.iter().position(|&b| !b.is_ascii() || !(b as char).is_whitespace());
+
+        match end {
+            None => true,
+            Some(i) if self.bytes[i].is_ascii() => false,
+            Some(i) => Bytes::new(&self.bytes[i..]).utf8_chars_lossy().all(char::is_whitespace),
+        }
+    }
+
+    /// Number of columns the bytes take up when each tab advances to the next
+    /// multiple of `tab_width`, and every other byte takes up one column. With
+    /// a `tab_width` of `1` this is the length of the bytes.
+    pub fn width(self, tab_width: usize) -> usize {
+        // Tabs take up a single column as well, so the bytes needn't be scanned.
+        if tab_width <= 1 {
+            return self.len();
+        }
+
+        self.bytes.iter().fold(0, |column, &b| match b {
+            b'\t' => (column / tab_width + 1) * tab_width,
+            _ => column + 1,
+        })
+    }
+
     /// Remove leading and trailing whitespace.
     ///
     /// Whitespace is identified by using `char::is_whitespace` on the char equivalent of a byte
@@ -120,7 +241,12 @@ impl<'a> Bytes<'a> {
         Bytes::new(b)
     }
 
-    /// Get an iterator over all lines.
+    /// Get an iterator over all lines, without their line endings.
+    ///
+    /// Lines are terminated by `\n`, `\r\n` or a lone `\r`. A terminator at
+    /// the end of the bytes doesn't start another line, so both `a\nb` and
+    /// `a\nb\n` have two lines, like `wc -l` counts for the latter. Empty
+    /// bytes have no lines, while `\n` is a single empty line.
     pub fn lines(self) -> Lines<'a> {
         Lines {
             buf: self.bytes,
@@ -129,7 +255,12 @@ impl<'a> Bytes<'a> {
 
     /// Check if the given byte is contained.
     pub fn contains(self, needle: &[u8]) -> bool {
-        self.bytes.windows(needle.len()).any(|w| w == needle)
+        memchr::memmem::find(self.bytes, needle).is_some()
+    }
+
+    /// Find the index of the first occurrence of the given bytes.
+    pub fn find(self, needle: &[u8]) -> Option<usize> {
+        memchr::memmem::find(self.bytes, needle)
     }
 
     /// Check if bytes array starts with the given value.
@@ -138,6 +269,12 @@ impl<'a> Bytes<'a> {
     }
 }
 
+impl<'a> AsRef<[u8]> for Bytes<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
 impl<'a> fmt::Display for Bytes<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         String::from_utf8_lossy(self.bytes).fmt(fmt)
@@ -176,7 +313,7 @@ impl<'a> Iterator for Lines<'a> {
         if let Some(mut idx) = memchr::memchr2(NL, CR, self.buf) {
             let o = &self.buf[..idx];
 
-            if self.buf[idx] == NL && self.buf.get(idx + 1).map(|c| *c == CR).unwrap_or(false) {
+            if self.buf[idx] == CR && self.buf.get(idx + 1).map(|c| *c == NL).unwrap_or(false) {
                 idx += 1;
             }
 
@@ -196,9 +333,101 @@ impl<'a> Iterator for Lines<'a> {
 mod tests {
     use super::Bytes;
 
+    #[test]
+    fn test_is_binary() {
+        let utf16 = b"\xff\xfea\x00b\x00";
+        assert!(super::is_binary(utf16));
+        assert!(!super::is_binary_with(utf16, 8000, true));
+        assert!(!super::is_binary_with(b"\x00\x00\xfe\xffa\x00\x00\x00", 8000, true));
+        assert!(super::is_binary_with(b"\x7fELF\x00", 8000, true));
+        assert!(!super::is_binary_with(b"abcd\x00", 4, false));
+    }
+
+    #[test]
+    fn test_is_binary_stray_null() {
+        assert!(!super::is_binary(b"fn main() {\n    let x = 1;\x00\n}\n"));
+        assert!(!super::is_binary(b"no null bytes \x01\x02\x03\x04"));
+        assert!(super::is_binary(b"\x00\x01\x02\x03ab\xff\xfe\x80"));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(&b"ab"[..], &*super::decode(b"ab").unwrap().0);
+        assert_eq!(&b"ab"[..], &*super::decode(b"\xef\xbb\xbfab").unwrap().0);
+        assert_eq!(&b"ab"[..], &*super::decode(b"\xff\xfea\x00b\x00").unwrap().0);
+        assert_eq!(&b"ab"[..], &*super::decode(b"\xfe\xff\x00a\x00b").unwrap().0);
+        assert_eq!("caf\u{e9}".as_bytes(), &*super::decode(b"caf\xe9").unwrap().0);
+    }
+
+    #[test]
+    fn test_decode_encoding() {
+        let encoding = |bytes: &[u8]| super::decode(bytes).unwrap().1;
+
+        assert_eq!(encoding(b"ab"), "UTF-8");
+        assert_eq!(encoding(b"\xef\xbb\xbfab"), "UTF-8");
+        assert_eq!(encoding(b"\xff\xfea\x00b\x00"), "UTF-16LE");
+        assert_eq!(encoding(b"\xfe\xff\x00a\x00b"), "UTF-16BE");
+        assert_eq!(encoding(b"caf\xe9"), super::LATIN_1);
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_encoding_name() {
+        assert_eq!(super::encoding_name("UTF-16BE"), Some("UTF-16BE"));
+        assert_eq!(super::encoding_name(super::LATIN_1), Some(super::LATIN_1));
+        assert_eq!(super::encoding_name("unknown"), None);
+    }
+
+    #[test]
+    fn test_strip_utf8_bom() {
+        assert_eq!(&b"ab"[..], super::strip_utf8_bom(b"\xef\xbb\xbfab"));
+        assert_eq!(&b"ab"[..], super::strip_utf8_bom(b"ab"));
+        assert_eq!(&b"\xff\xfea\x00"[..], super::strip_utf8_bom(b"\xff\xfea\x00"));
+    }
+
+    #[test]
+    fn test_width() {
+        assert_eq!(5, Bytes::new(b"a\tb c").width(1));
+        assert_eq!(7, Bytes::new(b"a\tb c").width(4));
+        assert_eq!(9, Bytes::new(b"\t  \tx").width(4));
+        assert_eq!(8, Bytes::new(b"\t\t").width(4));
+        assert_eq!(2, Bytes::new(b"\t\t").width(0));
+    }
+
     #[test]
     fn test_lines() {
-        assert_eq!(5, Bytes::new(b"foo\nbar\n\rbaz\r\rtail").lines().count());
+        assert_eq!(6, Bytes::new(b"foo\nbar\n\rbaz\r\rtail").lines().count());
+    }
+
+    #[test]
+    fn test_lines_endings() {
+        fn lines(bytes: &[u8]) -> Vec<&[u8]> {
+            Bytes::new(bytes).lines().map(Bytes::as_bytes).collect()
+        }
+
+        assert!(lines(b"").is_empty());
+        assert_eq!(vec![&b""[..]], lines(b"\n"));
+        assert_eq!(vec![&b"a"[..], b"b"], lines(b"a\nb"));
+        assert_eq!(vec![&b"a"[..], b"b"], lines(b"a\nb\n"));
+        assert_eq!(vec![&b"a"[..], b"b"], lines(b"a\r\nb\r\n"));
+        assert_eq!(vec![&b"a"[..], b"", b"b"], lines(b"a\r\n\r\nb"));
+    }
+
+    #[test]
+    fn test_is_whitespace() {
+        let cases: &[&[u8]] = &[
+            b"", b" \t\r\n", b"\x0b\x0c", b"\x1c", b" a ",
+            "\u{a0}".as_bytes(), " \u{2003} ".as_bytes(), " \u{a0}b".as_bytes(),
+            b" \xa0", b"\x85", b"\xc2",
+        ];
+
+        for &case in cases {
+            let expected = Bytes::new(case).utf8_chars_lossy().all(char::is_whitespace);
+            assert_eq!(expected, Bytes::new(case).is_whitespace(), "{:?}", case);
+        }
+
+        assert!(Bytes::new("\u{a0}".as_bytes()).is_whitespace());
+        assert!(!Bytes::new(b" \xa0").is_whitespace());
     }
 
     #[test]
@@ -226,6 +455,12 @@ mod tests {
         assert!(Bytes::new(b"foobar").contains(b"oob"));
     }
 
+    #[test]
+    fn test_find() {
+        assert_eq!(None, Bytes::new(b"foobar").find(b"baz"));
+        assert_eq!(Some(3), Bytes::new(b"foobar").find(b"bar"));
+    }
+
     #[test]
     fn test_starts_with() {
         assert!(!Bytes::new(b"foobar").contains(b"baz"));
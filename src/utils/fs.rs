@@ -2,41 +2,192 @@
 // Use of this source code is governed by the APACHE2.0/MIT licence that can be
 // found in the LICENCE-{APACHE/MIT} file.
 
-use std::collections::BTreeMap;
-use std::error::Error;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::Hasher;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::time::SystemTime;
 
 use ignore::WalkBuilder;
 use ignore::overrides::OverrideBuilder;
 use ignore::WalkState::*;
 
 use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
 
 // This is just a re-export from the auto generated file.
 pub use language::get_filetype_from_shebang;
-use language::{Language, LanguageType};
+use cache::StatsCache;
+use config::WalkConfig;
+use language::{parse_custom_with_config, too_large_stats, Language, LanguageType, Languages};
 use file_access::FileAccess;
+use stats::Stats;
 
 /// Populate statistics from files.
 pub fn get_all_files(paths: &[&str],
                      ignored_directories: Vec<&str>,
-                     languages: &mut BTreeMap<LanguageType, Language>,
+                     languages: &mut Languages,
                      types: Option<Vec<LanguageType>>)
+{
+    log_errors(try_get_all_files(paths, ignored_directories, languages, types));
+}
+
+/// Populate statistics from files like `get_all_files`, returning the paths
+/// which couldn't be read together with their errors instead of logging them.
+pub fn try_get_all_files(paths: &[&str],
+                         ignored_directories: Vec<&str>,
+                         languages: &mut Languages,
+                         types: Option<Vec<LanguageType>>)
+    -> Vec<(PathBuf, io::Error)>
+{
+    let globs = ignored_globs(&ignored_directories);
+    let globs: Vec<_> = globs.iter().map(|glob| &**glob).collect();
+
+    try_get_all_files_with_globs(paths, &globs, languages, types, &WalkConfig::default())
+}
+
+/// Parses files like `try_get_all_files`, calling `on_file` with the name,
+/// language and statistics of each file as soon as it's parsed instead of
+/// adding them to `languages`, whose settings are still used. Files of custom
+/// languages aren't reported, and identical files aren't deduplicated.
+pub fn for_each_file<F>(paths: &[&str],
+                        ignored_directories: Vec<&str>,
+                        languages: &Languages,
+                        types: Option<Vec<LanguageType>>,
+                        on_file: F)
+    -> Vec<(PathBuf, io::Error)>
+    where F: Fn(&str, LanguageType, Stats) + Send + Sync
+{
+    let globs = ignored_globs(&ignored_directories);
+    let globs: Vec<_> = globs.iter().map(|glob| &**glob).collect();
+    let config = WalkConfig::default();
+
+    let (files, mut errors) = walk_files(paths, &globs, &config, false);
+
+    let results = with_threads(config.threads, || {
+        let files = files.iter().map(|path| &**path);
+        parse_file_accesses(files, languages, types.as_deref(), Some(&on_file))
+    });
+
+    errors.extend(results.into_iter().filter_map(|result| result.err())
+        .map(|(path, error, _)| (path, error)));
+    errors
+}
+
+/// Globs excluding each of the ignored directories.
+fn ignored_globs(ignored_directories: &[&str]) -> Vec<String> {
+    ignored_directories.iter()
+        .map(|ignored| format!("!{}", ignored))
+        .collect()
+}
+
+/// Populate statistics from files, filtered by a list of glob patterns.
+///
+/// Globs use gitignore syntax. A glob prefixed with `!` excludes the files it
+/// matches, while any other glob is an include pattern: once one is provided,
+/// only files matching an include pattern are counted. These globs take
+/// precedence over `.gitignore` and `.ignore` files, so an include pattern
+/// also counts files that would otherwise have been ignored.
+///
+/// Which ignore files are respected is controlled by `config`. Besides
+/// `.gitignore` and `.ignore` files, tokei specific `.tokeignore` files with
+/// the same syntax are respected. These take precedence over the other ignore
+/// files, while the globs take precedence over all of them.
+pub fn get_all_files_with_globs(paths: &[&str],
+                                globs: &[&str],
+                                languages: &mut Languages,
+                                types: Option<Vec<LanguageType>>,
+                                config: &WalkConfig)
+{
+    log_errors(try_get_all_files_with_globs(paths, globs, languages, types, config));
+}
+
+/// Populate statistics from files like `get_all_files_with_globs`, returning
+/// the paths which couldn't be read together with their errors instead of
+/// logging them.
+pub fn try_get_all_files_with_globs(paths: &[&str],
+                                    globs: &[&str],
+                                    languages: &mut Languages,
+                                    types: Option<Vec<LanguageType>>,
+                                    config: &WalkConfig)
+    -> Vec<(PathBuf, io::Error)>
+{
+    let (files, mut errors) = walk_files(paths, globs, config, languages.dedup);
+
+    errors.extend(with_threads(config.threads, || {
+        try_get_all_file_accesses(files.iter().map(|path| &**path), languages, types)
+    }));
+
+    errors
+}
+
+/// Walks the paths like `try_get_all_files_with_globs`, returning the files
+/// found together with the paths which couldn't be walked. The files are
+/// sorted by their paths if `sorted` is set.
+fn walk_files(paths: &[&str], globs: &[&str], config: &WalkConfig, sorted: bool)
+    -> (Vec<PathBuf>, Vec<(PathBuf, io::Error)>)
 {
     let (tx, rx) = mpsc::channel();
 
-    let mut paths = paths.iter();
-    let mut walker = WalkBuilder::new(paths.next().unwrap());
+    let vcs_root = if config.vcs_root { find_vcs_root(Path::new(paths[0])) } else { None };
+
+    // Inside of a repository the paths are walked canonicalized, so that
+    // globs can be matched relative to its root.
+    let walked: Vec<_> = paths.iter()
+        .map(|&path| match vcs_root {
+            Some(_) => (fs::canonicalize(path).unwrap_or_else(|_| path.into()), path),
+            None => (PathBuf::from(path), path),
+        })
+        .collect();
+
+    // The files and errors are named after the provided paths again.
+    let restore = |path: &Path| -> PathBuf {
+        if vcs_root.is_none() {
+            return path.to_path_buf();
+        }
+
+        for (walked, original) in &walked {
+            if let Ok(rest) = path.strip_prefix(walked) {
+                return if rest.as_os_str().is_empty() {
+                    PathBuf::from(original)
+                } else {
+                    Path::new(original).join(rest)
+                };
+            }
+        }
+
+        path.to_path_buf()
+    };
+
+    let mut walker = WalkBuilder::new(&walked[0].0);
 
-    for path in paths {
+    for (path, _) in &walked[1..] {
         walker.add(path);
     }
 
-    if !ignored_directories.is_empty() {
-        let mut overrides = OverrideBuilder::new(".");
+    walker.git_ignore(config.git_ignore)
+        .git_global(config.git_ignore)
+        .git_exclude(config.git_ignore)
+        .hidden(config.hidden)
+        .ignore(config.ignore)
+        .parents(config.parents || vcs_root.is_some())
+        .threads(config.threads)
+        .follow_links(config.follow_links);
+
+    if config.ignore {
+        walker.add_custom_ignore_filename(".tokeignore");
+    }
+
+    if !globs.is_empty() {
+        let root = vcs_root.as_deref().unwrap_or_else(|| Path::new("."));
+        let mut overrides = OverrideBuilder::new(root);
 
-        for ignored in ignored_directories {
-            rs_error!(overrides.add(&format!("!{}", ignored)));
+        for glob in globs {
+            rs_error!(overrides.add(glob));
         }
 
         walker.overrides(overrides.build().expect("Excludes provided were invalid"));
@@ -48,21 +199,14 @@ pub fn get_all_files(paths: &[&str],
             let entry = match entry {
                 Ok(entry) => entry,
                 Err(error) => {
-                    use ignore::Error;
-                    if let Error::WithDepth { err: ref error, .. } = error {
-                        if let Error::WithPath { ref path, err: ref error } = **error {
-                            error!("{} reading {}", error.description(), path.display());
-                            return Continue;
-                        }
-                    }
-                    error!("{}", error.description());
+                    tx.send(Err(walk_error(error))).unwrap();
                     return Continue;
                 }
             };
 
             if let Some(file_type) = entry.file_type() {
                 if file_type.is_file() {
-                    tx.send(entry).unwrap();
+                    tx.send(Ok(entry)).unwrap();
                 }
             }
 
@@ -70,8 +214,89 @@ pub fn get_all_files(paths: &[&str],
         })
     });
 
-    let files: Vec<_> = rx.into_iter().collect();
-    get_all_file_accesses(files.iter().map(|e| e.path()), languages, types)
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+
+    for entry in rx {
+        match entry {
+            Ok(entry) => files.push(entry),
+            Err((path, error)) => errors.push((restore(&path), error)),
+        }
+    }
+
+    // Sorted so that the same file is kept of several links or identical
+    // files.
+    if config.follow_links || sorted {
+        files.sort_by(|a, b| a.path().cmp(b.path()));
+    }
+
+    // The same file can be reached through several links, only count it once.
+    if config.follow_links {
+        let mut seen = HashSet::new();
+        files.retain(|entry| {
+            fs::canonicalize(entry.path()).map(|path| seen.insert(path)).unwrap_or(true)
+        });
+    }
+
+    let files = files.iter().map(|entry| restore(entry.path())).collect();
+    (files, errors)
+}
+
+/// Finds the root of the git repository containing `path`, which is the first
+/// of its canonicalized parent directories containing a `.git` directory, or
+/// a `.git` file in the case of worktrees and submodules.
+fn find_vcs_root(path: &Path) -> Option<PathBuf> {
+    let path = fs::canonicalize(path).ok()?;
+
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Converts an error from the directory walker into the path it occurred at
+/// and an `io::Error`. The path is empty if the error isn't tied to one.
+fn walk_error(error: ignore::Error) -> (PathBuf, io::Error) {
+    use ignore::Error;
+
+    match error {
+        Error::WithDepth { err, .. } => walk_error(*err),
+        Error::WithPath { path, err } => (path, walk_error(*err).1),
+        Error::Loop { child, .. } => {
+            let error = io::Error::new(io::ErrorKind::Other, "file system loop found");
+            (child, error)
+        }
+        Error::Io(error) => (PathBuf::new(), error),
+        error => (PathBuf::new(), io::Error::new(io::ErrorKind::Other, error.to_string())),
+    }
+}
+
+fn log_errors(errors: Vec<(PathBuf, io::Error)>) {
+    for (path, error) in errors {
+        if path.as_os_str().is_empty() {
+            error!("{}", error);
+        } else {
+            error!("{} reading {}", error, path.display());
+        }
+    }
+}
+
+/// Runs `f` inside of a rayon thread pool with the given number of threads,
+/// or in the global thread pool if `threads` is `0`.
+fn with_threads<F, R>(threads: usize, f: F) -> R
+    where F: FnOnce() -> R + Send,
+          R: Send,
+{
+    if threads == 0 {
+        return f();
+    }
+
+    match ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(f),
+        Err(error) => {
+            error!("{}", error);
+            f()
+        }
+    }
 }
 
 /// Populate statistics from `FileAccess` objects.
@@ -80,33 +305,257 @@ pub fn get_all_files(paths: &[&str],
 /// files to be analyzed from other places, like in-memory or from an archive.
 pub fn get_all_file_accesses<'a, I: 'a, F>(
     paths: I,
-    languages: &mut BTreeMap<LanguageType, Language>,
+    languages: &mut Languages,
     types: Option<Vec<LanguageType>>,
 ) where
     I: IntoIterator<Item = F>,
     F: Send + FileAccess<'a>,
+{
+    log_errors(try_get_all_file_accesses(paths, languages, types));
+}
+
+/// Populate statistics from `FileAccess` objects like `get_all_file_accesses`,
+/// returning the names of the files which couldn't be read together with
+/// their errors instead of logging them.
+pub fn try_get_all_file_accesses<'a, I: 'a, F>(
+    paths: I,
+    languages: &mut Languages,
+    types: Option<Vec<LanguageType>>,
+) -> Vec<(PathBuf, io::Error)> where
+    I: IntoIterator<Item = F>,
+    F: Send + FileAccess<'a>,
 {
     let types: Option<&[LanguageType]> = types.as_ref().map(|v| &**v);
+    let results = parse_file_accesses(paths, languages, types, None);
+
+    let mut errors = Vec::new();
+    // The cache is replaced by the files counted now.
+    let mut new_cache = languages.cache.as_ref()
+        .map(|_| StatsCache::with_config(&languages.config));
+    let mut hashes = HashSet::new();
+
+    for result in results {
+        // Results are in the order of the files, so the first is kept. Files
+        // of different languages are never identical.
+        if let Ok((ref parsed, _, Some(contents))) = result {
+            let language = match *parsed {
+                Parsed::Known(language_type, _) => Ok(language_type),
+                Parsed::Custom(index, _) => Err(index),
+            };
+
+            if !hashes.insert((language, contents)) {
+                continue;
+            }
+        }
+
+        let (language_type, mut stats) = match result {
+            Ok((Parsed::Known(language_type, stats), modified, _)) => {
+                // Files which weren't counted depend on the settings rather
+                // than their contents, so they aren't cached.
+                let counted = !stats.too_large && !stats.generated;
+
+                if let (Some(cache), Some((time, size))) = (new_cache.as_mut(), modified) {
+                    if counted {
+                        cache.insert(stats.name.clone(), time, size, language_type, stats.clone());
+                    }
+                }
 
-    let iter: Vec<_> = paths
+                (language_type, stats)
+            }
+            Ok((Parsed::Custom(index, stats), _, _)) => {
+                let name = languages.custom_languages[index].name.clone();
+                let entry = languages.custom.entry(name).or_default();
+
+                if stats.too_large {
+                    entry.mark_inaccurate();
+                }
+
+                entry.add_stat(stats);
+                continue;
+            }
+            Err((path, error, skipped)) => {
+                match skipped {
+                    Some(Skipped::Known(language_type)) => {
+                        languages.entry(language_type).or_default().add_skipped();
+                    }
+                    Some(Skipped::Custom(index)) => {
+                        let name = languages.custom_languages[index].name.clone();
+                        languages.custom.entry(name).or_default().add_skipped();
+                    }
+                    None => {}
+                }
+
+                errors.push((path, error));
+                continue;
+            }
+        };
+
+        // Attribute embedded blocks to their own languages.
+        for (child_type, children) in mem::take(&mut stats.children) {
+            let entry = languages.entry(child_type).or_default();
+
+            for child in children {
+                entry.add_stat(child);
+            }
+        }
+
+        let entry = languages.entry(language_type).or_insert_with(Language::new);
+
+        if stats.too_large {
+            entry.mark_inaccurate();
+        }
+
+        entry.add_stat(stats);
+    }
+
+    if new_cache.is_some() {
+        languages.cache = new_cache;
+    }
+
+    errors
+}
+
+/// Parses the files in parallel with the settings of `languages`, returning
+/// the result of each file in their order. Files of known languages are
+/// passed to `on_file` instead, if it's provided.
+fn parse_file_accesses<'a, I, F>(
+    paths: I,
+    languages: &Languages,
+    types: Option<&[LanguageType]>,
+    on_file: Option<&OnFile>,
+) -> Vec<FileResult> where
+    I: IntoIterator<Item = F>,
+    F: Send + FileAccess<'a>,
+{
+    let custom_languages = &languages.custom_languages;
+    let progress = languages.progress.get();
+    let cache = languages.cache.as_ref();
+    let dedup = languages.dedup && on_file.is_none();
+    let config = &languages.config;
+    // Files cached with other settings are parsed again.
+    let current = cache.filter(|cache| cache.is_for(config));
+
+    paths
         .into_iter()
         .collect::<Vec<_>>()
         .into_par_iter()
         .filter_map(|file_access| {
-            match LanguageType::parse(file_access, types) {
-                Ok(out) => return out,
-                Err(e) => {
-                    error!("{} reading {}", e.description(), file_access.name());
-                    return None;
+            // Custom languages take precedence, and aren't filtered by `types`.
+            let custom = custom_languages.iter()
+                .position(|language| language.matches(file_access));
+
+            // Files of custom languages aren't cached.
+            let modified = match (cache, custom) {
+                (Some(_), None) => file_access.modified(),
+                _ => None,
+            };
+
+            let cached = match (current, modified) {
+                (Some(cache), Some((time, size))) => cache.get(&file_access.name(), time, size),
+                _ => None,
+            };
+
+            // The contents are hashed from the text read for parsing.
+            let mut contents = None;
+            let mut on_text = |text: &[u8]| if dedup {
+                contents = Some(content_hash(text));
+            };
+
+            let result = match (custom, cached) {
+                (Some(index), _) => {
+                    let mut text = Vec::new();
+                    file_access.open()
+                        .and_then(|reader| config.read_to_end(reader, &mut text))
+                        .and_then(|fits| if fits {
+                            on_text(&text);
+                            let language = &custom_languages[index];
+                            parse_custom_with_config(language, file_access.name(), &text, config)
+                        } else {
+                            Ok(too_large_stats(file_access.name(), config))
+                        })
+                        .map(|stats| Some(Parsed::Custom(index, stats)))
+                }
+                (None, Some((language, stats))) => {
+                    let is_supported = config.is_supported(types, language);
+
+                    // Cached files are only read to be deduplicated.
+                    if dedup && is_supported && !stats.too_large {
+                        let mut text = Vec::new();
+                        let read = file_access.open()
+                            .and_then(|reader| config.read_to_end(reader, &mut text));
+
+                        if let Ok(true) = read {
+                            on_text(&text);
+                        }
+                    }
+
+                    Ok(Some(Parsed::Known(language, stats.clone())).filter(|_| is_supported))
+                }
+                (None, None) => {
+                    LanguageType::parse_with_config_and_text(file_access, types, config, on_text)
+                        .map(|out| out.map(|(language, stats)| Parsed::Known(language, stats)))
+                }
+            };
+
+            if let Some(progress) = progress {
+                progress.file_done(&file_access.name());
+            }
+
+            match (result, on_file) {
+                (Ok(Some(Parsed::Known(language, stats))), Some(on_file)) => {
+                    on_file(&file_access.name(), language, stats);
+                    None
+                }
+                (Ok(Some(Parsed::Custom(..))), Some(_)) => None,
+                (Ok(out), _) => out.map(|parsed| Ok((parsed, modified, contents))),
+                (Err(e), _) => {
+                    // Files whose language is known from their name are
+                    // still attributed to it, so it can be marked inaccurate.
+                    let skipped = match custom {
+                        Some(index) => Some(Skipped::Custom(index)),
+                        None => config.overrides.get(file_access)
+                            .or_else(|| LanguageType::from_file_access(file_access))
+                            .filter(|&language| config.is_supported(types, language))
+                            .map(Skipped::Known),
+                    };
+
+                    Some(Err((PathBuf::from(&*file_access.name()), e, skipped)))
                 }
             }
         })
-        .collect();
+        .collect()
+}
 
-    for (language_type, stats) in iter {
-        let entry = languages.entry(language_type).or_insert_with(Language::new);
-        entry.add_stat(stats);
-    }
+/// The length and hash of the contents of a file, to only count identical
+/// files once when `Languages::set_dedup` is set. The length is compared as
+/// well, so that a collision of the hashes alone doesn't drop a file.
+fn content_hash(text: &[u8]) -> (usize, u64) {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(text);
+    (text.len(), hasher.finish())
+}
+
+/// A callback receiving the name, language and statistics of each file.
+type OnFile<'a> = dyn Fn(&str, LanguageType, Stats) + Sync + 'a;
+
+/// The result of parsing a file, with its modification time and size if it's
+/// cached, and the length and hash of its contents if files are deduplicated.
+type FileResult = Result<
+    (Parsed, Option<(SystemTime, u64)>, Option<(usize, u64)>),
+    (PathBuf, io::Error, Option<Skipped>),
+>;
+
+/// The statistics of a file, counted either as one of tokei's languages or as
+/// the custom language at the index.
+enum Parsed {
+    Known(LanguageType, Stats),
+    Custom(usize, Stats),
+}
+
+/// The language a file which couldn't be parsed would have been counted as.
+enum Skipped {
+    Known(LanguageType),
+    Custom(usize),
 }
 
 #[cfg(test)]
@@ -116,6 +565,7 @@ mod test {
     use std::fs::create_dir;
     use language::languages::Languages;
     use language::LanguageType;
+    use config::ParseConfig;
     use self::tempdir::TempDir;
 
     #[test]
@@ -129,4 +579,562 @@ mod test {
 
         assert!(l.get(&LanguageType::Rust).is_none());
     }
+
+    #[test]
+    fn walker_globs() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let vendor = tmp_dir.path().join("vendor");
+        create_dir(&vendor).expect("Couldn't create vendor within temp");
+        write(tmp_dir.path().join("app.js"), "let x = 1;\n").unwrap();
+        write(tmp_dir.path().join("app.min.js"), "let x = 1;\n").unwrap();
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(vendor.join("lib.js"), "let x = 1;\n").unwrap();
+
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &["!*.min.js", "!vendor"], &mut l, None, &WalkConfig::default());
+        assert_eq!(l[&LanguageType::JavaScript].stats.len(), 1);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &["*.rs"], &mut l, None, &WalkConfig::default());
+        assert!(l.get(&LanguageType::JavaScript).is_none());
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+    }
+
+    #[test]
+    fn walker_config() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        create_dir(tmp_dir.path().join(".git")).unwrap();
+        write(tmp_dir.path().join(".gitignore"), "generated.rs\n").unwrap();
+        write(tmp_dir.path().join("generated.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join(".hidden.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &[], &mut l, None, &WalkConfig::default());
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+
+        let config = WalkConfig {
+            git_ignore: false,
+            hidden: false,
+            threads: 1,
+            ..WalkConfig::default()
+        };
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &[], &mut l, None, &config);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walker_follow_links() {
+        use std::fs::write;
+        use std::os::unix::fs::symlink;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let shared = tmp_dir.path().join("shared");
+        create_dir(&shared).unwrap();
+        write(shared.join("lib.rs"), "fn main() {}\n").unwrap();
+        symlink(&shared, tmp_dir.path().join("linked")).unwrap();
+        symlink(tmp_dir.path(), shared.join("cycle")).unwrap();
+
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &[], &mut l, None, &WalkConfig::default());
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+
+        let config = WalkConfig {
+            follow_links: true,
+            ..WalkConfig::default()
+        };
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &[], &mut l, None, &config);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+    }
+
+    #[test]
+    fn file_access_errors() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let main = tmp_dir.path().join("main.rs");
+        write(&main, "fn main() {}\n").unwrap();
+        let missing = tmp_dir.path().join("missing.rs");
+
+        let mut l = Languages::new();
+        let files = vec![&*main, &*missing];
+        let errors = try_get_all_file_accesses(files, &mut l, None);
+
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, missing);
+        assert_eq!(errors[0].1.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn skipped_files() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let main = tmp_dir.path().join("main.rs");
+        write(&main, "fn main() {}\n").unwrap();
+        let binary = tmp_dir.path().join("binary.rs");
+        write(&binary, b"\x00\x01\x02\x03").unwrap();
+        let unknown = tmp_dir.path().join("missing.unknown");
+
+        let mut l = Languages::new();
+        let files = vec![&*main, &*binary, &*unknown];
+        let errors = try_get_all_file_accesses(files, &mut l, None);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(l.len(), 1);
+
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!(rust.stats.len(), 1);
+        assert_eq!(rust.skipped, 1);
+        assert!(rust.inaccurate);
+    }
+
+    #[test]
+    fn statistics_from_paths() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let main = tmp_dir.path().join("main.rs");
+        write(&main, "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("other.rs"), "fn main() {}\n").unwrap();
+
+        let mut l = Languages::new();
+        l.get_statistics_from_paths(vec![main.clone()], None);
+
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!(rust.stats.len(), 1);
+        assert_eq!(rust.stats[0].name, main.to_string_lossy());
+        assert_eq!(rust.code, 1);
+    }
+
+    #[test]
+    fn walker_tokeignore() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let generated = tmp_dir.path().join("generated");
+        create_dir(&generated).unwrap();
+        write(tmp_dir.path().join(".tokeignore"), "generated/\n").unwrap();
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(generated.join("lib.rs"), "fn main() {}\n").unwrap();
+
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        get_all_files(&[path], vec![], &mut l, None);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+
+        let config = WalkConfig {
+            ignore: false,
+            ..WalkConfig::default()
+        };
+
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[path], &[], &mut l, None, &config);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 2);
+    }
+
+    #[test]
+    fn custom_languages() {
+        use std::fs::write;
+        use language::CustomLanguage;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.acme"), "-- A\n\nrun\n").unwrap();
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let mut l = Languages::new();
+        l.register_custom(CustomLanguage {
+            name: "Acme".to_owned(),
+            extensions: vec!["ACME".to_owned()],
+            line_comments: vec!["--".to_owned()],
+            ..CustomLanguage::default()
+        });
+
+        l.get_statistics(&[tmp_dir.path().to_str().unwrap()], vec![], Some(vec![LanguageType::Rust]));
+
+        let acme = &l.custom()["Acme"];
+        assert_eq!(acme.stats.len(), 1);
+        assert_eq!((acme.code, acme.comments, acme.blanks), (1, 1, 1));
+        assert_eq!(l[&LanguageType::Rust].code, 1);
+    }
+
+    #[test]
+    fn extension_overrides() {
+        use std::fs::write;
+        use config::ExtensionOverrides;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.h"), "class A {};\n").unwrap();
+        write(tmp_dir.path().join("main.c"), "int main() {}\n").unwrap();
+        write(tmp_dir.path().join("BUILD"), "cc_library()\n").unwrap();
+
+        let mut overrides = ExtensionOverrides::default();
+        overrides.extensions.insert("H".to_owned(), LanguageType::CppHeader);
+        overrides.file_names.insert("build".to_owned(), LanguageType::Python);
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig { overrides, ..ParseConfig::default() });
+        l.get_statistics(&[tmp_dir.path().to_str().unwrap()], vec![], None);
+
+        assert_eq!(l[&LanguageType::CppHeader].stats.len(), 1);
+        assert_eq!(l[&LanguageType::C].stats.len(), 1);
+        assert_eq!(l[&LanguageType::Python].stats.len(), 1);
+        assert!(!l.contains_key(&LanguageType::CHeader));
+    }
+
+    #[test]
+    fn progress() {
+        use std::fs::write;
+        use std::sync::{Arc, Mutex};
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("binary.rs"), b"\x00\x01").unwrap();
+        write(tmp_dir.path().join("README"), "Hello\n").unwrap();
+
+        let done = Arc::new(Mutex::new(Vec::new()));
+        let mut l = Languages::new();
+        let reported = done.clone();
+        l.set_progress(move |path: &str| reported.lock().unwrap().push(path.to_owned()));
+        l.get_statistics(&[tmp_dir.path().to_str().unwrap()], vec![], None);
+
+        let mut done = done.lock().unwrap().clone();
+        done.sort();
+
+        let expected = ["README", "binary.rs", "main.rs"].iter()
+            .map(|name| tmp_dir.path().join(name).to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(done, expected);
+    }
+
+    #[test]
+    fn stats_cache() {
+        use std::fs::{write, File};
+        use std::time::{Duration, SystemTime};
+        use cache::StatsCache;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let main = tmp_dir.path().join("main.rs");
+        let lib = tmp_dir.path().join("lib.rs");
+        write(&main, "fn main() {}\n").unwrap();
+        write(&lib, "// A\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_cache(StatsCache::new());
+        l.get_statistics(&[path], vec![], None);
+        let cache = l.take_cache().unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // Overwriting a file with the same size and modification time isn't
+        // noticed, while changing its size is.
+        let modified = File::open(&main).unwrap().metadata().unwrap().modified().unwrap();
+        write(&main, "// main() {}\n").unwrap();
+        File::options().write(true).open(&main).unwrap().set_modified(modified).unwrap();
+        write(&lib, "// A\nfn a() {}\n").unwrap();
+
+        let mut l = Languages::new();
+        l.set_cache(cache);
+        l.get_statistics(&[path], vec![], None);
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!((rust.code, rust.comments, rust.blanks), (2, 1, 0));
+        let cache = l.take_cache().unwrap();
+
+        // Changing the modification time is noticed, and removed files are
+        // dropped from the cache.
+        let later = SystemTime::now() + Duration::from_secs(60);
+        File::options().write(true).open(&main).unwrap().set_modified(later).unwrap();
+        std::fs::remove_file(&lib).unwrap();
+
+        let mut l = Languages::new();
+        l.set_cache(cache);
+        l.get_statistics(&[path], vec![], None);
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!((rust.code, rust.comments, rust.blanks), (0, 1, 0));
+        assert_eq!(l.take_cache().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn stats_cache_config() {
+        use std::fs::write;
+        use cache::StatsCache;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        write(tmp_dir.path().join("b.rs"), "fn b() {}\n".repeat(100)).unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_cache(StatsCache::new());
+        l.get_statistics(&[path], vec![], None);
+        let cache = l.take_cache().unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // Files cached with other settings are parsed again, and files which
+        // were too large to be read aren't cached.
+        let mut l = Languages::new();
+        l.set_cache(cache);
+        l.set_parse_config(ParseConfig { max_file_bytes: Some(100), ..ParseConfig::default() });
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l[&LanguageType::Rust].files_too_large, 1);
+        let cache = l.take_cache().unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let mut l = Languages::new();
+        l.set_cache(cache);
+        l.get_statistics(&[path], vec![], None);
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!((rust.code, rust.files_too_large), (101, 0));
+    }
+
+    #[test]
+    fn dedup() {
+        use std::fs::{create_dir, write};
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        create_dir(tmp_dir.path().join("vendor")).unwrap();
+        write(tmp_dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        write(tmp_dir.path().join("vendor/a.rs"), "fn a() {}\n").unwrap();
+        write(tmp_dir.path().join("vendor/b.rs"), "fn b() {}\n// B\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 3);
+
+        let mut l = Languages::new();
+        l.set_dedup(true);
+        l.get_statistics(&[path], vec![], None);
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!((rust.code, rust.comments), (2, 1));
+
+        let mut names: Vec<_> = rust.stats.iter().map(|stats| stats.name.clone()).collect();
+        names.sort();
+        let expected = ["a.rs", "vendor/b.rs"].iter()
+            .map(|name| tmp_dir.path().join(name).to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn dedup_languages() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("__init__.py"), "").unwrap();
+        write(tmp_dir.path().join("mod.rs"), "").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_dedup(true);
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l[&LanguageType::Python].stats.len(), 1);
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 1);
+    }
+
+    #[test]
+    fn max_file_bytes() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        write(tmp_dir.path().join("b.rs"), "fn b() {}\n".repeat(100)).unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig { max_file_bytes: Some(100), ..ParseConfig::default() });
+        l.get_statistics(&[path], vec![], None);
+
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!(rust.code, 1);
+        assert!(rust.inaccurate);
+        assert_eq!(rust.stats.iter().filter(|stats| stats.too_large).count(), 1);
+        assert_eq!((rust.files_too_large, rust.files_blank_only), (1, 0));
+    }
+
+    #[test]
+    fn skip_generated() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("schema.rs"), "// @generated\nfn a() {}\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig { skip_generated: true, ..ParseConfig::default() });
+        l.get_statistics(&[path], vec![], None);
+
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!(rust.code, 1);
+        assert_eq!(rust.files, 2);
+        assert_eq!(rust.files_generated, 1);
+        assert_eq!((rust.files_with_code, rust.files_blank_only), (1, 0));
+    }
+
+    #[test]
+    fn todo_markers() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.rs"), "// TODO: a\n// REVIEW: b\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig {
+            todo_markers: vec!["REVIEW".to_owned()],
+            ..ParseConfig::default()
+        });
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l[&LanguageType::Rust].todos, 1);
+    }
+
+    #[test]
+    fn tab_width() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.rs"), "fn main() {\n\tx();\n}\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig { tab_width: 8, ..ParseConfig::default() });
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l[&LanguageType::Rust].max_line_length, 12);
+    }
+
+    #[test]
+    fn count_shebang_as_comment() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.py"), "#!/usr/bin/env python\nx = 1\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig { count_shebang_as_comment: true, ..ParseConfig::default() });
+        l.get_statistics(&[path], vec![], None);
+
+        let python = &l[&LanguageType::Python];
+        assert_eq!((python.code, python.comments), (1, 1));
+    }
+
+    #[test]
+    fn binary_sniff_bytes() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        let text = format!("fn main() {{}}\n// {}\n", "\0".repeat(100));
+        write(tmp_dir.path().join("main.rs"), text).unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l[&LanguageType::Rust].skipped, 1);
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig { binary_sniff_bytes: 8, ..ParseConfig::default() });
+        l.get_statistics(&[path], vec![], None);
+
+        let rust = &l[&LanguageType::Rust];
+        assert_eq!((rust.code, rust.comments, rust.skipped), (1, 1, 0));
+    }
+
+    #[test]
+    fn excluded_types() {
+        use std::fs::write;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("README.md"), "# Title\n").unwrap();
+        write(tmp_dir.path().join("data.json"), "{}\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig {
+            excluded_types: vec![LanguageType::Markdown, LanguageType::Json],
+            ..ParseConfig::default()
+        });
+        l.get_statistics(&[path], vec![], None);
+        assert_eq!(l.keys().collect::<Vec<_>>(), vec![&LanguageType::Rust]);
+
+        let mut l = Languages::new();
+        l.set_parse_config(ParseConfig {
+            excluded_types: vec![LanguageType::Markdown],
+            ..ParseConfig::default()
+        });
+        l.get_statistics(&[path], vec![], Some(vec![LanguageType::Markdown, LanguageType::Json]));
+        assert_eq!(l.keys().collect::<Vec<_>>(), vec![&LanguageType::Json]);
+    }
+
+    #[test]
+    fn vcs_root() {
+        use std::fs::{create_dir, write};
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        create_dir(tmp_dir.path().join(".git")).unwrap();
+        create_dir(tmp_dir.path().join("sub")).unwrap();
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("sub/a.rs"), "fn a() {}\n").unwrap();
+        write(tmp_dir.path().join("sub/b.rs"), "fn b() {}\n").unwrap();
+        let sub = tmp_dir.path().join("sub");
+        let sub = sub.to_str().unwrap();
+
+        // Globs are matched relative to the current directory by default.
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[sub], &["!/sub/b.rs"], &mut l, None, &WalkConfig::default());
+        assert_eq!(l[&LanguageType::Rust].stats.len(), 2);
+
+        let config = WalkConfig { vcs_root: true, ..WalkConfig::default() };
+        let mut l = Languages::new();
+        get_all_files_with_globs(&[sub], &["!/sub/b.rs"], &mut l, None, &config);
+
+        let names = l[&LanguageType::Rust].stats.iter()
+            .map(|stats| stats.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [tmp_dir.path().join("sub/a.rs").to_string_lossy()]);
+    }
+
+    #[test]
+    fn for_each_file() {
+        use std::fs::write;
+        use std::sync::Mutex;
+
+        let tmp_dir = TempDir::new("test").expect("Couldn't create temp dir");
+        write(tmp_dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        write(tmp_dir.path().join("lib.py"), "# A\nx = 1\n").unwrap();
+        write(tmp_dir.path().join("README.md"), "# Title\n").unwrap();
+        let path = tmp_dir.path().to_str().unwrap();
+
+        let files = Mutex::new(Vec::new());
+        let languages = Languages::new();
+        let types = Some(vec![LanguageType::Rust, LanguageType::Python]);
+        let errors = languages.for_each_file(&[path], vec![], types, |_, language, stats| {
+            files.lock().unwrap().push((language, stats.code, stats.comments));
+        });
+
+        let mut files = files.into_inner().unwrap();
+        files.sort();
+        assert!(errors.is_empty());
+        assert_eq!(files, [(LanguageType::Python, 1, 1), (LanguageType::Rust, 1, 0)]);
+        assert!(languages.is_empty());
+    }
 }
@@ -0,0 +1,23 @@
+# 23 lines 14 code 7 comments 2 blanks
+#include "macros.h"
+#define STDOUT 1
+; NASM comments start with a semicolon.
+section .data
+    msg db "Hello; world", 10   ; not a comment inside the string
+
+section .text
+# GAS comments start with a hash.
+/* The C preprocessor is often run
+   over GAS sources. */
+#ifdef __x86_64__
+_start:
+    mov rax, 1      # write
+    mov rdi, 1
+    // AArch64 uses double slashes.
+    lea rsi, [rel msg]
+
+    mov rdx, 13
+    syscall
+    /* done */
+    ret
+#endif
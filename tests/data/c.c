@@ -0,0 +1,16 @@
+// 16 lines 8 code 5 comments 3 blanks
+#include <stdio.h>
+
+/* A string with comment markers inside of it. */
+static const char *greeting = "Hello /* world */ // !";
+
+/*
+ * Entry point.
+ */
+int main(void)
+{
+    char c = 'x'; // trailing comment
+    printf("%s %c\n", greeting, c);
+
+    return 0; /* end */
+}
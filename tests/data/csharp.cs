@@ -0,0 +1,17 @@
+// 17 lines 12 code 4 comments 1 blanks
+using System;
+
+/* Multi-line
+   comment */
+class Program
+{
+    // entry point
+    static void Main()
+    {
+        var path = @"C:\dir\"; // ends with a backslash
+        var text = @"line one // not a comment
+/* also not a comment { }
+""quoted"" }";
+        Console.WriteLine(path + text);
+    }
+}
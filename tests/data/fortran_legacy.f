@@ -0,0 +1,14 @@
+C 14 lines 9 code 4 comments 1 blanks
+      PROGRAM HELLO
+* A star comment
+c a lowercase comment
+      INTEGER I
+  100 CONTINUE
+      WRITE (*,*) 'Hello, world!'
+     *  , I
+     C  , 'continued'
+      I = I + 1 ! inline comment
+
+      IF (I .LT. 10) GOTO 100
+      ! indented comment
+      END
@@ -1,4 +1,4 @@
-# 22 lines 11 code 7 comments 4 blanks
+# 31 lines 16 code 9 comments 6 blanks
 variable "foo" "bar" {
   default = "yes"
 }
@@ -19,4 +19,13 @@ locals {
     yep  = "nope" # bad comment
     nope = "yep"
   }
+
+  name = "${lookup(var.names, "first")} /* not a comment"
+  # comment after an interpolation
+
+  policy = <<-EOT
+    # not a comment
+    /* not a comment either
+    EOT
+  // comment after a heredoc
 }
@@ -0,0 +1,15 @@
+# 15 lines 8 code 4 comments 3 blanks
+
+# A comment
+{ pkgs }:
+
+/* A multi line
+   comment */
+pkgs.mkShell {
+  name = "shell # not a comment";
+
+  shellHook = ''
+    # not a comment ${pkgs.hello}
+    echo "/* not a comment */"
+  '';
+}
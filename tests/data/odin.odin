@@ -0,0 +1,16 @@
+// 16 lines 7 code 5 comments 4 blanks
+package main
+
+import "core:fmt"
+
+/* Block comments
+   /* nest */
+   in Odin */
+main :: proc() {
+    path := `C:\not\a\comment //`
+    r := '"'
+
+    // Comment
+    fmt.println("/* not a comment */", path, r)
+}
+
@@ -0,0 +1,17 @@
+# 17 lines 11 code 3 comments 3 blanks
+"""Module docstring with "quotes" and 'apostrophes'.
+
+# not a comment
+// not a comment either
+"""
+
+def greet(name):
+    '''Say hello.
+    # still the docstring, "quoted" too
+    '''
+    # a real comment
+    message = "Hello, 'friend' # not a comment"
+    return message  # trailing comment
+
+# another comment
+print(greet("world"))
@@ -1,4 +1,4 @@
-// 11 lines 5 code 3 comments 3 blanks
+// 17 lines 9 code 4 comments 4 blanks
 
 /// Documentation comment
 pub fn main() void {
@@ -7,5 +7,11 @@ pub fn main() void {
     // Leading-comment
     const b = c"line comment embedded //";
     const c = \\line comment embedded // //
+
+    const d =
+        \\ "unclosed quote
+        \\ /* unclosed comment
+    ;
+    // Comment after a multiline string
 }
 